@@ -0,0 +1,101 @@
+// Compares the specialized small-weight searches against `PrioritySearchIter`,
+// which is backed by the priority-queue crate. Run with `cargo bench`.
+use std::time::{Duration, Instant};
+
+use advent2024::{
+    multidim::{DirType, ManhattanDir, Position},
+    search_iter::{DialIter, PrioritySearchIter, ZeroOneBfsIter},
+};
+use enum_iterator::all;
+
+const SIDE: isize = 300;
+const REPETITIONS: usize = 5;
+
+fn main() {
+    let walls = random_walls();
+    let open =
+        |p: Position| (0..SIDE).contains(&p[0]) && (0..SIDE).contains(&p[1]) && !walls[index(p)];
+
+    // Day 16 style: moving forward costs 1 and turning costs 1000.
+    let reindeer = |&(p, f): &(Position, ManhattanDir)| {
+        let mut result = vec![
+            ((p, f.clockwise()), 1000),
+            ((p, f.counterclockwise()), 1000),
+        ];
+        let ahead = f.neighbor(p);
+        if open(ahead) {
+            result.push(((ahead, f), 1));
+        }
+        result
+    };
+    let start = (Position::default(), ManhattanDir::E);
+    let heap = time(|| {
+        let mut searcher = PrioritySearchIter::dijkstra(start, reindeer);
+        searcher.by_ref().last();
+        searcher.all_costs().len()
+    });
+    let buckets = time(|| DialIter::new(start, 1000, reindeer).count());
+    report("turns 1000, steps 1", heap, buckets, "Dial");
+
+    // Moving within a row is free; changing rows costs 1.
+    let rows = |p: &Position| {
+        all::<ManhattanDir>()
+            .map(|d| d.neighbor(*p))
+            .filter(|n| open(*n))
+            .map(|n| (n, if n[1] == p[1] { 0 } else { 1 }))
+            .collect::<Vec<_>>()
+    };
+    let heap = time(|| {
+        let mut searcher = PrioritySearchIter::dijkstra(Position::default(), rows);
+        searcher.by_ref().last();
+        searcher.all_costs().len()
+    });
+    let deque = time(|| ZeroOneBfsIter::new(Position::default(), rows).count());
+    report("row changes cost 1", heap, deque, "0-1 BFS");
+}
+
+fn index(p: Position) -> usize {
+    (p[1] * SIDE + p[0]) as usize
+}
+
+// Roughly a fifth of the cells are walls, keeping the corner around the origin open.
+fn random_walls() -> Vec<bool> {
+    let mut state = 0x5eed_u64;
+    let mut walls = (0..SIDE * SIDE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % 5 == 0
+        })
+        .collect::<Vec<_>>();
+    for x in 0..3 {
+        for y in 0..3 {
+            walls[index(Position::from((x, y)))] = false;
+        }
+    }
+    walls
+}
+
+// Best of several runs, along with the number of nodes the search reached.
+fn time<F: FnMut() -> usize>(mut search: F) -> (Duration, usize) {
+    (0..REPETITIONS)
+        .map(|_| {
+            let start = Instant::now();
+            let reached = search();
+            (start.elapsed(), reached)
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, heap: (Duration, usize), special: (Duration, usize), special_name: &str) {
+    assert_eq!(heap.1, special.1);
+    println!(
+        "{name}: {} nodes, priority queue {:.2} ms, {special_name} {:.2} ms ({:.1}x)",
+        heap.1,
+        heap.0.as_secs_f64() * 1000.0,
+        special.0.as_secs_f64() * 1000.0,
+        heap.0.as_secs_f64() / special.0.as_secs_f64()
+    );
+}
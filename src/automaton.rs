@@ -0,0 +1,254 @@
+use std::{collections::HashMap, hash::Hash, marker::PhantomData};
+
+use enum_iterator::all;
+
+use crate::{
+    grid::{GridWorld, InfiniteGrid, InfiniteGridValue},
+    multidim::{Dir, DirType, Position},
+};
+
+// A grid that a `CellularAutomaton` can update in lockstep.
+pub trait AutomatonWorld: Clone + Eq + Hash {
+    type Value: Copy;
+
+    // Every position whose value could change during the next step.
+    fn cells_to_update(&self) -> Vec<Position>;
+
+    // None for positions outside a bounded world.
+    fn cell(&self, p: Position, wrap: bool) -> Option<Self::Value>;
+
+    fn set_cell(&mut self, p: Position, value: Self::Value);
+}
+
+impl<V: Copy + Eq + Hash> AutomatonWorld for GridWorld<V> {
+    type Value = V;
+
+    fn cells_to_update(&self) -> Vec<Position> {
+        self.position_iter().collect()
+    }
+
+    fn cell(&self, p: Position, wrap: bool) -> Option<V> {
+        if wrap {
+            self.value(p % Position::from((self.width() as isize, self.height() as isize)))
+        } else {
+            self.value(p)
+        }
+    }
+
+    fn set_cell(&mut self, p: Position, value: V) {
+        self.update(p, value);
+    }
+}
+
+// Toroidal wrap has no meaning for an unbounded grid, so it is ignored.
+impl<V: InfiniteGridValue + Eq + Hash> AutomatonWorld for InfiniteGrid<V> {
+    type Value = V;
+
+    fn cells_to_update(&self) -> Vec<Position> {
+        let mut result = self.iter().map(|(p, _)| *p).collect::<Vec<_>>();
+        for (p, _) in self.iter() {
+            result.extend(all::<Dir>().map(|d| d.neighbor(*p)));
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    fn cell(&self, p: Position, _wrap: bool) -> Option<V> {
+        Some(self.get_pos(p))
+    }
+
+    fn set_cell(&mut self, p: Position, value: V) {
+        self.add_pos(p, value);
+    }
+}
+
+// Each step computes every cell from its current value and its `D` neighborhood,
+// listed in `all::<D>()` order. Results are written to a second buffer so that
+// every cell sees the same generation.
+pub struct CellularAutomaton<D, G, R>
+where
+    D: DirType,
+    G: AutomatonWorld,
+    R: Fn(G::Value, &[Option<G::Value>]) -> G::Value,
+{
+    current: G,
+    buffer: G,
+    rule: R,
+    wrap: bool,
+    steps: usize,
+    dirs: PhantomData<D>,
+}
+
+impl<D, G, R> CellularAutomaton<D, G, R>
+where
+    D: DirType,
+    G: AutomatonWorld,
+    R: Fn(G::Value, &[Option<G::Value>]) -> G::Value,
+{
+    pub fn new(world: G, rule: R) -> Self {
+        Self {
+            buffer: world.clone(),
+            current: world,
+            rule,
+            wrap: false,
+            steps: 0,
+            dirs: PhantomData,
+        }
+    }
+
+    pub fn toroidal(world: G, rule: R) -> Self {
+        let mut result = Self::new(world, rule);
+        result.wrap = true;
+        result
+    }
+
+    pub fn world(&self) -> &G {
+        &self.current
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn step(&mut self) {
+        // The buffer may still hold cells from two generations ago, so they
+        // are rewritten as well.
+        let mut cells = self.current.cells_to_update();
+        cells.extend(self.buffer.cells_to_update());
+        cells.sort();
+        cells.dedup();
+        for p in cells {
+            if let Some(value) = self.current.cell(p, false) {
+                let neighbors = all::<D>()
+                    .map(|d| self.current.cell(d.neighbor(p), self.wrap))
+                    .collect::<Vec<_>>();
+                self.buffer.set_cell(p, (self.rule)(value, &neighbors));
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.buffer);
+        self.steps += 1;
+    }
+
+    // Steps until a previously seen state reappears.
+    // Returns the step at which the cycle begins and its period.
+    pub fn find_cycle(&mut self) -> (usize, usize) {
+        let mut seen = HashMap::new();
+        loop {
+            if let Some(start) = seen.insert(self.current.clone(), self.steps) {
+                return (start, self.steps - start);
+            }
+            self.step();
+        }
+    }
+
+    // Brings the automaton to `target` steps, skipping whole cycles once one
+    // repeats. Targets earlier than the current step count are not reachable.
+    pub fn fast_forward(&mut self, target: usize) {
+        let base = self.steps;
+        let mut seen = HashMap::new();
+        let mut states: Vec<G> = vec![];
+        while self.steps < target {
+            if let Some(start) = seen.insert(self.current.clone(), self.steps) {
+                let period = self.steps - start;
+                let offset = (target - start) % period;
+                self.current = states[start - base + offset].clone();
+                self.steps = target;
+                return;
+            }
+            states.push(self.current.clone());
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::{GridCharWorld, InfiniteGrid},
+        multidim::{Dir, Position},
+    };
+
+    use super::CellularAutomaton;
+
+    fn life(cell: char, neighbors: &[Option<char>]) -> char {
+        let alive = neighbors.iter().filter(|n| **n == Some('#')).count();
+        if alive == 3 || (cell == '#' && alive == 2) {
+            '#'
+        } else {
+            '.'
+        }
+    }
+
+    #[test]
+    fn test_blinker() {
+        let start = ".....
+..#..
+..#..
+..#..
+....."
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let mut automaton = CellularAutomaton::<Dir, _, _>::new(start.clone(), life);
+        automaton.step();
+        assert_eq!(
+            format!("{}", automaton.world()),
+            ".....
+.....
+.###.
+.....
+....."
+        );
+        automaton.step();
+        assert_eq!(*automaton.world(), start);
+
+        let mut automaton = CellularAutomaton::<Dir, _, _>::new(start.clone(), life);
+        assert_eq!(automaton.find_cycle(), (0, 2));
+        automaton.fast_forward(1_000_001);
+        assert_eq!(automaton.steps(), 1_000_001);
+        assert_ne!(*automaton.world(), start);
+    }
+
+    #[test]
+    fn test_toroidal_glider() {
+        let start = ".#...
+..#..
+###..
+.....
+....."
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let mut automaton = CellularAutomaton::<Dir, _, _>::toroidal(start.clone(), life);
+        assert_eq!(automaton.find_cycle(), (0, 20));
+
+        let mut bounded = CellularAutomaton::<Dir, _, _>::new(start, life);
+        let (cycle_start, period) = bounded.find_cycle();
+        assert_eq!(period, 1);
+        assert!(cycle_start > 0);
+    }
+
+    #[test]
+    fn test_infinite_glider() {
+        let mut start = InfiniteGrid::<u8>::default();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            start.add(x, y, 1);
+        }
+        let mut automaton =
+            CellularAutomaton::<Dir, _, _>::new(start.clone(), |cell, neighbors| {
+                let alive = neighbors.iter().filter(|n| **n == Some(1)).count();
+                if alive == 3 || (cell == 1 && alive == 2) {
+                    1
+                } else {
+                    0
+                }
+            });
+        for _ in 0..4 {
+            automaton.step();
+        }
+        let mut expected = InfiniteGrid::default();
+        for (p, v) in start.iter() {
+            expected.add_pos(*p + Position::from((1, 1)), *v);
+        }
+        assert_eq!(*automaton.world(), expected);
+    }
+}
@@ -3,12 +3,11 @@ use std::collections::BTreeMap;
 use advent2024::{
     advent_main,
     grid::GridDigitWorld,
-    multidim::{DirType, ManhattanDir, Position},
+    multidim::{ManhattanDir, Position},
     search_iter::BfsIter,
     Part,
 };
 use bare_metal_modulo::{MNum, ModNumC};
-use enum_iterator::all;
 use hash_histogram::HashHistogram;
 
 fn main() -> anyhow::Result<()> {
@@ -106,9 +105,10 @@ fn ascending_neighbors(
     topomap: &GridDigitWorld,
 ) -> impl Iterator<Item = Position> + '_ {
     let target_height = topomap.value(p).unwrap().a() + 1;
-    all::<ManhattanDir>()
-        .map(move |d| d.neighbor(p))
-        .filter(move |n| topomap.value(*n).map_or(false, |h| h.a() == target_height))
+    topomap
+        .neighbors::<ManhattanDir>(p)
+        .into_iter()
+        .filter(move |n| topomap.value(*n).unwrap().a() == target_height)
 }
 
 fn num_distinct_paths(start: &Position, topomap: &GridDigitWorld) -> usize {
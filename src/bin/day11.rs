@@ -1,5 +1,4 @@
-use advent2024::{advent_main, all_lines, Part};
-use hash_histogram::HashHistogram;
+use advent2024::{advent_main, all_lines, memo::Memo, Part};
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, _| {
@@ -7,42 +6,27 @@ fn main() -> anyhow::Result<()> {
             Part::One => 25,
             Part::Two => 75,
         };
-        let mut table = StoneTable::new(filename)?;
-        for _ in 0..iterations {
-            table.blink();
+        let line = all_lines(filename)?.next().unwrap();
+        // Each stone after `iterations` blinks is the end of one path from
+        // (stone, blinks remaining) down to a state with no blinks left.
+        let mut memo = Memo::<(Stone, usize), u128>::new();
+        let mut total = 0;
+        for sn in line.split_whitespace() {
+            let stone = Stone::new(sn.parse::<u128>().unwrap());
+            total += memo
+                .count_paths(&(stone, iterations), successors, |(_, left)| *left == 0)
+                .unwrap();
         }
-        println!("{}", table.count());
+        println!("{total}");
         Ok(())
     })
 }
 
-struct StoneTable {
-    blinks2stones: Vec<HashHistogram<Stone, u128>>,
-}
-
-impl StoneTable {
-    fn new(filename: &str) -> anyhow::Result<Self> {
-        let line = all_lines(filename)?.next().unwrap();
-        Ok(Self {
-            blinks2stones: vec![line
-                .split_whitespace()
-                .map(|sn| Stone::new(sn.parse::<u128>().unwrap()))
-                .collect()],
-        })
-    }
-
-    fn count(&self) -> u128 {
-        self.blinks2stones.last().unwrap().total_count()
-    }
-
-    fn blink(&mut self) {
-        let mut new_line = HashHistogram::new();
-        for (stone, count) in self.blinks2stones.last().unwrap().iter() {
-            for new_stone in stone.blink() {
-                new_line.bump_by(&new_stone, *count);
-            }
-        }
-        self.blinks2stones.push(new_line);
+fn successors((stone, left): &(Stone, usize)) -> Vec<(Stone, usize)> {
+    if *left == 0 {
+        vec![]
+    } else {
+        stone.blink().into_iter().map(|s| (s, left - 1)).collect()
     }
 }
 
@@ -1,10 +1,9 @@
-use std::{cmp::min, collections::HashMap};
+use std::{cmp::min, collections::HashMap, iter::once};
 
 use advent2024::{
     advent_main,
     grid::GridCharWorld,
     multidim::{Dir, DirType, ManhattanDir, Position},
-    search_iter::BfsIter,
     Part,
 };
 use enum_iterator::all;
@@ -118,14 +117,9 @@ fn bfs_points2regions(garden: &GridCharWorld) -> HashMap<Position, usize> {
     let mut result = HashMap::new();
     for (p, v) in garden.position_value_iter() {
         if !result.contains_key(p) {
-            BfsIter::new(*p, |s| {
-                result.insert(*s, current);
-                all::<ManhattanDir>()
-                    .map(|d| d.neighbor(*s))
-                    .filter(|n| garden.value(*n).map_or(false, |c| c == *v))
-                    .collect()
-            })
-            .last();
+            for s in garden.reachable_from::<ManhattanDir, _, _>(once(*p), |c| c == *v) {
+                result.insert(s, current);
+            }
             current += 1;
         }
     }
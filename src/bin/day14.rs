@@ -1,9 +1,9 @@
-use std::collections::HashMap;
-
 use advent2024::{
     advent_main, all_lines,
+    cycles::brent,
     grid::GridCharWorld,
     multidim::{Dir, Position},
+    render::GridRenderer,
     Part,
 };
 use hash_histogram::HashHistogram;
@@ -44,25 +44,19 @@ fn safety_score(mut robots: Vec<Robot>, dimensions: Position) {
 }
 
 fn find_repeat(mut robots: Vec<Robot>, dimensions: Position) {
-    let mut seconds = 0;
-    let mut seen = HashMap::new();
-    loop {
+    let (start, period) = brent(&robots, |robots| {
+        let mut robots = robots.clone();
+        advance_all_robots(&mut robots, dimensions);
+        robots
+    });
+    for seconds in 0..start + period {
         let central = central_column_count(&robots, dimensions);
         if central > 10 {
             println!("{central} {seconds}s");
         }
         advance_all_robots(&mut robots, dimensions);
-        seconds += 1;
-        match seen.get(&robots) {
-            None => {
-                seen.insert(robots.clone(), seconds);
-            }
-            Some(s) => {
-                println!("Repeated {s} at {seconds}");
-                break;
-            }
-        }
     }
+    println!("Repeated {start} at {}", start + period);
 }
 
 fn advance_all_robots(robots: &mut Vec<Robot>, dimensions: Position) {
@@ -89,15 +83,18 @@ fn central_column_count(robots: &Vec<Robot>, dimensions: Position) -> usize {
 // https://www.perplexity.ai/search/write-a-tutorial-on-using-curs-0oe7KdRIRaa7qoqtnL.S_A
 fn visualize(mut robots: Vec<Robot>, dimensions: Position) {
     let mut seconds = 0;
+    let mut status = String::new();
     let window = initscr();
     window.keypad(true);
     noecho();
 
     loop {
         window.clear();
-        window.addstr(format!("time: {seconds}s\n"));
+        window.addstr(format!("time: {seconds}s {status}\n"));
         window.addstr(format!("{}", render(&robots, dimensions)));
-        match window.getch() {
+        let key = window.getch();
+        status.clear();
+        match key {
             Some(Input::Character(c)) => match c {
                 'a' | 'f' => {
                     seconds += 1;
@@ -114,6 +111,15 @@ fn visualize(mut robots: Vec<Robot>, dimensions: Position) {
                         break;
                     }
                 },
+                's' => {
+                    let filename = format!("day14_{seconds}s.pbm");
+                    status = match GridRenderer::new(&render(&robots, dimensions))
+                        .save_pbm(filename.as_str(), |c| c == 'O')
+                    {
+                        Ok(()) => format!("(saved {filename})"),
+                        Err(e) => format!("(could not save {filename}: {e})"),
+                    };
+                }
                 'q' => break,
                 _ => {}
             },
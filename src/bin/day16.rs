@@ -1,25 +1,31 @@
-use std::collections::HashSet;
+use std::{cmp::Ordering, collections::HashSet, ops::Add};
 
 use advent2024::{
     advent_main,
     grid::GridCharWorld,
     multidim::{DirType, ManhattanDir, Position},
-    search_iter::{BfsIter, PrioritySearchIter},
+    render::{GridRenderer, Style},
+    search_iter::{DialIter, PrioritySearchIter},
     Part,
 };
 use enum_iterator::all;
 use itertools::Itertools;
+use num::Zero;
 
 const MOVE_COST: usize = 1;
 const TURN_COST: usize = 1000;
+const STEP: Moves = Moves { turns: 0, steps: 1 };
+const TURN: Moves = Moves { turns: 1, steps: 0 };
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, options| {
         let maze = GridCharWorld::from_char_file(filename)?;
         let start = Reindeer::new(maze.any_position_for('S'), ManhattanDir::E);
         let end = maze.any_position_for('E');
-        let mut searcher = PrioritySearchIter::dijkstra(start, successor_func(&maze));
+        let mut searcher =
+            PrioritySearchIter::dijkstra(start, successor_func(&maze)).tracking_all_parents();
         match part {
+            Part::One if options.contains(&"-dial") => part1_dial(start, end, &maze),
             Part::One => part1(end, &mut searcher),
             Part::Two => part2(end, &maze, &mut searcher, options.contains(&"-show")),
         }
@@ -27,74 +33,126 @@ fn main() -> anyhow::Result<()> {
     })
 }
 
-fn successor_func(maze: &GridCharWorld) -> impl Fn(&Reindeer) -> Vec<(Reindeer, usize)> + '_ {
+fn successor_func(maze: &GridCharWorld) -> impl Fn(&Reindeer) -> Vec<(Reindeer, Moves)> + '_ {
     |s: &Reindeer| {
-        [
-            (s.forward(), MOVE_COST),
-            (s.left(), TURN_COST),
-            (s.right(), TURN_COST),
-        ]
-        .iter()
-        .filter(|(p, _)| maze.value(p.p).map_or(false, |v| v != '#'))
-        .copied()
-        .collect()
+        [(s.forward(), STEP), (s.left(), TURN), (s.right(), TURN)]
+            .iter()
+            .filter(|(p, _)| maze.value(p.p).is_some_and(|v| v != '#'))
+            .copied()
+            .collect()
     }
 }
 
-fn part1<S: FnMut(&Reindeer) -> Vec<(Reindeer, usize)>, H: Fn(&Reindeer) -> Option<usize>>(
+fn part1<S: FnMut(&Reindeer) -> Vec<(Reindeer, Moves)>, H: Fn(&Reindeer) -> Option<Moves>>(
     end: Position,
-    searcher: &mut PrioritySearchIter<usize, Reindeer, S, H>,
+    searcher: &mut PrioritySearchIter<Moves, Reindeer, S, H>,
 ) {
+    let at_goal = searcher.find(|r| r.p == end).unwrap();
+    let score = searcher.cost_for(&at_goal).score();
+    println!("{score}");
+}
+
+// Scores only grow by 1 or 1000, so a bucket queue can stand in for the heap.
+fn part1_dial(start: Reindeer, end: Position, maze: &GridCharWorld) {
+    let successor = successor_func(maze);
+    let mut searcher = DialIter::new(start, TURN_COST, |r| {
+        successor(r)
+            .into_iter()
+            .map(|(n, moves)| (n, moves.score()))
+            .collect()
+    });
     let at_goal = searcher.find(|r| r.p == end).unwrap();
     let score = searcher.cost_for(&at_goal);
     println!("{score}");
 }
 
-fn part2<S: FnMut(&Reindeer) -> Vec<(Reindeer, usize)>, H: Fn(&Reindeer) -> Option<usize>>(
+fn part2<S: FnMut(&Reindeer) -> Vec<(Reindeer, Moves)>, H: Fn(&Reindeer) -> Option<Moves>>(
     end: Position,
     maze: &GridCharWorld,
-    searcher: &mut PrioritySearchIter<usize, Reindeer, S, H>,
+    searcher: &mut PrioritySearchIter<Moves, Reindeer, S, H>,
     show: bool,
 ) {
     searcher.by_ref().last();
-    let mut on_path = HashSet::new();
-    BfsIter::new(end, |p| {
-        on_path.insert(*p);
-        let outgoing_dirs = all::<ManhattanDir>()
-            .filter(|d| on_path.contains(&d.neighbor(*p)))
-            .collect_vec();
-        let candidates = all::<ManhattanDir>()
-            .map(|d| Reindeer::new(d.neighbor(*p), d.inverse()))
-            .filter(|r| maze.value(r.p).map_or(false, |v| v != '#'))
-            .collect_vec();
-        let costs = candidates
-            .iter()
-            .map(|r| {
-                searcher.cost_for(r)
-                    + if outgoing_dirs.contains(&r.f) {
-                        0
-                    } else {
-                        TURN_COST
-                    }
-            })
-            .collect_vec();
-        let min_cost = costs.iter().min().unwrap();
-        (0..costs.len())
-            .filter(|i| costs[*i] == *min_cost)
-            .map(|i| candidates[i].p)
-            .collect()
-    })
-    .last();
+    let end_states = all::<ManhattanDir>()
+        .map(|f| Reindeer::new(end, f))
+        .filter(|r| searcher.is_reachable(r))
+        .collect_vec();
+    let best = end_states
+        .iter()
+        .map(|r| searcher.cost_for(r))
+        .min()
+        .unwrap();
+    let on_path = searcher
+        .nodes_on_optimal_paths_to(
+            end_states
+                .into_iter()
+                .filter(|r| searcher.cost_for(r) == best),
+        )
+        .iter()
+        .map(|r| r.p)
+        .collect::<HashSet<_>>();
     if show {
-        let mut maze = maze.clone();
-        for p in on_path.iter() {
-            maze.update(*p, 'O');
-        }
-        println!("{maze}");
+        let renderer =
+            GridRenderer::new(maze).with_overlay(on_path.iter().copied(), Style::glyph('O'));
+        println!("{renderer}");
     }
     println!("{}", on_path.len());
 }
 
+// Turns and steps taken, ranked by the puzzle's score rather than lexicographically.
+#[derive(Copy, Clone, Debug, Default)]
+struct Moves {
+    turns: usize,
+    steps: usize,
+}
+
+impl Moves {
+    fn score(&self) -> usize {
+        self.turns * TURN_COST + self.steps * MOVE_COST
+    }
+}
+
+impl PartialEq for Moves {
+    fn eq(&self, other: &Self) -> bool {
+        self.score() == other.score()
+    }
+}
+
+impl Eq for Moves {}
+
+impl Ord for Moves {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for Moves {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Moves {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            turns: self.turns + rhs.turns,
+            steps: self.steps + rhs.steps,
+        }
+    }
+}
+
+impl Zero for Moves {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.turns == 0 && self.steps == 0
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 struct Reindeer {
     p: Position,
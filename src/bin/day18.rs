@@ -1,13 +1,11 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::BTreeSet;
 
 use advent2024::{
     advent_main, all_lines,
     grid::GridCharWorld,
-    multidim::{DirType, ManhattanDir, Position},
-    search_iter::BfsIter,
+    multidim::{ManhattanDir, Position},
     Part,
 };
-use enum_iterator::all;
 use pancurses::{endwin, initscr, noecho, Input};
 
 fn main() -> anyhow::Result<()> {
@@ -56,29 +54,12 @@ fn find_impassible(bombs: &Vec<Position>, dim: isize, goal: Position) -> Positio
 }
 
 fn find_exit(fallen_bombs: &BTreeSet<Position>, goal: Position, dim: isize) -> Option<usize> {
-    let start = Position::default();
-    let mut distances = HashMap::<Position, usize>::new();
-    distances.insert(start, 0);
-    BfsIter::new(start, |point| {
-        all::<ManhattanDir>()
-            .map(|d| d.neighbor(*point))
-            .filter(|neighbor| {
-                !fallen_bombs.contains(neighbor)
-                    && neighbor.values().all(|v| v >= 0 && v < dim as isize)
-            })
-            .map(|neighbor| {
-                let parent_distance = distances.get(point).copied().unwrap();
-                let current_distance = parent_distance + 1;
-                let prev_distance = distances.get(&neighbor);
-                if prev_distance.is_none() || current_distance < prev_distance.copied().unwrap() {
-                    distances.insert(neighbor, current_distance);
-                }
-                neighbor
-            })
-            .collect()
-    })
-    .find(|p| *p == goal)
-    .map(|p| distances.get(&p).copied().unwrap())
+    let mut grid = GridCharWorld::new(dim as usize, dim as usize, '.');
+    for bomb in fallen_bombs.iter() {
+        grid.update(*bomb, '#');
+    }
+    grid.shortest_path::<ManhattanDir, _>(Position::default(), goal, |v| v == '.')
+        .map(|path| path.len() - 1)
 }
 
 fn view(dim: isize, bombs: &Vec<Position>) {
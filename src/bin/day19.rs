@@ -1,5 +1,4 @@
-use advent2024::{advent_main, all_lines, Part};
-use hash_histogram::HashHistogram;
+use advent2024::{advent_main, all_lines, memo::Memo, Part};
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, _| {
@@ -55,17 +54,18 @@ impl Table {
     }
 
     fn solve(&self) -> usize {
-        let mut counts: HashHistogram<usize> = HashHistogram::new();
-        for p in (0..self.pos2towels.len()).rev() {
-            for towel in self.pos2towels[p].iter() {
-                let successor = p + self.towel_lengths[*towel];
-                if successor == self.pos2towels.len() {
-                    counts.bump(&p);
-                } else {
-                    counts.bump_by(&p, counts.count(&successor));
-                }
-            }
-        }
-        counts.count(&0)
+        // Positions in the pattern form a DAG, each towel stepping forward.
+        let end = self.pos2towels.len();
+        Memo::new()
+            .count_paths(
+                &0,
+                |p| {
+                    self.pos2towels.get(*p).map_or(vec![], |towels| {
+                        towels.iter().map(|t| p + self.towel_lengths[*t]).collect()
+                    })
+                },
+                |p| *p == end,
+            )
+            .unwrap()
     }
 }
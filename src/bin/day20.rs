@@ -4,6 +4,7 @@ use advent2024::{
     advent_main,
     grid::GridCharWorld,
     multidim::{DirType, ManhattanDir, Position},
+    search_iter::BidirectionalDistances,
     Part,
 };
 use enum_iterator::all;
@@ -48,9 +49,21 @@ struct Distances {
 
 impl Distances {
     fn new(maze: &GridCharWorld) -> Self {
-        let (no_cheat, dist2end) = step_map(&maze, 'E', 'S');
-        let (repeat, dist2start) = step_map(&maze, 'S', 'E');
-        assert_eq!(no_cheat, repeat);
+        let open_neighbors = |p: &Position| {
+            maze.passable_neighbors::<ManhattanDir, _>(*p, |v| v != '#')
+                .into_iter()
+                .map(|n| (n, 1))
+                .collect()
+        };
+        let distances = BidirectionalDistances::new(
+            [maze.any_position_for('S')].into_iter(),
+            [maze.any_position_for('E')].into_iter(),
+            open_neighbors,
+            open_neighbors,
+        );
+        let no_cheat = distances.shortest().unwrap();
+        let dist2start = distances.all_from_start().clone();
+        let dist2end = distances.all_to_goal().clone();
         let maze = maze.clone();
         Self {
             no_cheat,
@@ -112,27 +125,6 @@ impl Distances {
     }
 }
 
-fn step_map(
-    maze: &GridCharWorld,
-    start_char: char,
-    end_char: char,
-) -> (usize, HashMap<Position, usize>) {
-    let mut dist2end = HashMap::new();
-    let mut current = maze.any_position_for(start_char);
-    let mut dist = 0;
-    loop {
-        dist2end.insert(current, dist);
-        if maze.value(current).unwrap() == end_char {
-            return (dist, dist2end);
-        }
-        dist += 1;
-        current = all::<ManhattanDir>()
-            .map(|d| d.neighbor(current))
-            .find(|p| maze.value(*p).unwrap() != '#' && !dist2end.contains_key(p))
-            .unwrap();
-    }
-}
-
 fn find_cheat_min(options: &Vec<&str>) -> usize {
     for opt in options.iter() {
         if opt.starts_with("-min") {
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use advent2024::{advent_main, all_lines, Part};
+use advent2024::{advent_main, all_lines, parallel::parallel_map, Part};
 use hash_histogram::HashHistogram;
 use num::Integer;
 
@@ -57,16 +57,21 @@ fn part1(filename: &str) -> anyhow::Result<()> {
 }
 
 fn part2(filename: &str) -> anyhow::Result<()> {
+    let lines = all_lines(filename)?
+        .map(|line| line.parse::<i128>().unwrap())
+        .collect::<Vec<_>>();
     let mut options = HashHistogram::new();
-    for line in all_lines(filename)? {
-        let line = line.parse::<i128>().unwrap();
-        update_option_totals(line, &mut options);
+    for change_map in parallel_map(&lines, |line| first_prices(*line)) {
+        for (option, value) in change_map.iter() {
+            options.bump_by(option, *value);
+        }
     }
     println!("{:?}", options.ranking_with_counts()[0]);
     Ok(())
 }
 
-fn update_option_totals(line: i128, options: &mut HashHistogram<Changes>) {
+// The price after each sequence of four changes first appears.
+fn first_prices(line: i128) -> HashMap<Changes, usize> {
     let mut changes = Changes::default();
     let mut sequence = SecretNumberSequence::new(line).take(2000);
     let mut prev = sequence.by_ref().next().unwrap().mod_floor(&10);
@@ -81,9 +86,7 @@ fn update_option_totals(line: i128, options: &mut HashHistogram<Changes>) {
         }
         prev = digit;
     }
-    for (option, value) in change_map.iter() {
-        options.bump_by(option, *value);
-    }
+    change_map
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
@@ -125,6 +128,9 @@ fn periods(filename: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+// Reports the first repeated value within 2000 steps. Brent's algorithm
+// would need up to a further cycle length of values to confirm the same
+// repeat, so it misses some that this scan finds.
 fn period(start: i128) -> Option<(usize, usize)> {
     let mut starts = HashMap::new();
     for (i, n) in SecretNumberSequence::new(start).enumerate().take(2000) {
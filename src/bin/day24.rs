@@ -1,10 +1,14 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap}, fmt::Display, str::FromStr, sync::Arc
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 use advent2024::{
     advent_main, all_lines,
-    graph::{graphviz_directed, AdjacencySets},
+    graph::{AdjacencySets, DotGraph},
+    parallel::parallel_find_any_stoppable,
     search_iter::BfsIter,
     Part,
 };
@@ -19,8 +23,7 @@ fn main() -> anyhow::Result<()> {
         } else if options.contains(&"-singles") {
             show_single_ancestors(circuit);
         } else if options.contains(&"-dot") {
-            let (graph, labels) = circuit.directed_edges();
-            graphviz_directed(graph.iter().cloned(), "day24.dot", &labels)?;
+            write_dot(&circuit, "day24.dot")?;
         } else {
             match part {
                 Part::One => part1(circuit),
@@ -31,6 +34,21 @@ fn main() -> anyhow::Result<()> {
     })
 }
 
+fn write_dot(circuit: &Circuit, filename: &str) -> anyhow::Result<()> {
+    let (graph, labels) = circuit.directed_edges();
+    let mut dot = DotGraph::from_edges(true, graph.iter().cloned());
+    for ((src, dest), label) in labels {
+        dot = dot.with_edge_attribute(src, dest, "label", label.as_str());
+    }
+    if let Some(bad_zs) = circuit.bad_zs().filter(|zs| !zs.is_empty()) {
+        let ancestors = circuit.bad_z_ancestors();
+        dot = dot
+            .highlight_nodes(ancestors.iter().map(|g| g.output().to_string()), "orange")
+            .highlight_nodes(bad_zs, "red");
+    }
+    dot.write(filename)
+}
+
 fn part1(mut circuit: Circuit) {
     circuit.run_to_completion();
     println!("{}", circuit.extract_num_with("z"));
@@ -40,24 +58,43 @@ fn part2(circuit: Circuit) {
     let mut graph = AdjacencySets::default();
     let (edges, _) = circuit.directed_edges();
     for (src, dest) in edges {
-        graph.connect(src.as_str(), dest.as_str());
+        graph.connect(&src, &dest);
     }
     let graph = graph;
     let in_degrees = graph.in_degrees();
-    let topo = graph.topologial_ordering().unwrap();
+    let topo = graph.topologial_ordering().unwrap_or_else(|| {
+        panic!("Gate cycle: {:?}", graph.find_cycle().unwrap());
+    });
     let topo_output = topo
         .iter()
         .filter(|s| *(in_degrees.get(*s).unwrap()) == 2)
         .cloned()
         .collect_vec();
-    let pairs = BTreeMap::new();
-    if !search(&circuit, &topo_output, 0, &pairs) {
+    // Each worker takes a first swap and searches the rest sequentially,
+    // abandoning its search once another worker succeeds.
+    let first_swaps = (0..topo_output.len())
+        .flat_map(|i| ((i + 1)..topo_output.len()).map(move |j| (i, j)))
+        .collect_vec();
+    let found = parallel_find_any_stoppable(&first_swaps, |(i, j), stop| {
+        let mut pairs = BTreeMap::new();
+        pairs.insert(topo_output[*i].to_string(), topo_output[*j].to_string());
+        search(&circuit, &topo_output, j + 1, &pairs, stop)
+    });
+    if found.is_none() {
         println!("Failed");
     }
 }
 
-fn search(circuit: &Circuit, topo_output: &Vec<String>, start: usize, pairs: &BTreeMap<String,String>) -> bool {
-    if pairs.len() == 4 {
+fn search(
+    circuit: &Circuit,
+    topo_output: &Vec<String>,
+    start: usize,
+    pairs: &BTreeMap<String, String>,
+    stop: &AtomicBool,
+) -> bool {
+    if stop.load(Ordering::Relaxed) {
+        false
+    } else if pairs.len() == 4 {
         let test = circuit.swapped_output_pairs(&pairs);
         if let Some(bad_zs) = test.bad_zs() {
             if bad_zs.len() == 0 {
@@ -75,12 +112,9 @@ fn search(circuit: &Circuit, topo_output: &Vec<String>, start: usize, pairs: &BT
     } else {
         for i in start..topo_output.len() {
             for j in (i + 1)..topo_output.len() {
-                if start == 0 {
-                    println!("From ({}, {})", i, j);
-                }
                 let mut pairs = pairs.clone();
                 pairs.insert(topo_output[i].to_string(), topo_output[j].to_string());
-                if search(circuit, topo_output, j + 1, &pairs) {
+                if search(circuit, topo_output, j + 1, &pairs, stop) {
                     return true;
                 }
             }
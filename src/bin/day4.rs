@@ -1,10 +1,9 @@
 use advent2024::{
     advent_main,
-    grid::GridCharWorld,
-    multidim::{Dir, DirType},
+    grid::{GridCharWorld, Orientation},
+    multidim::Dir,
     Part,
 };
-use enum_iterator::all;
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, _| {
@@ -19,30 +18,12 @@ fn main() -> anyhow::Result<()> {
 }
 
 fn part1(world: GridCharWorld) -> usize {
-    let target = vec!['X', 'M', 'A', 'S'];
-    world
-        .position_iter()
-        .map(|p| {
-            all::<Dir>()
-                .map(|d| world.values_from(p, d, target.len()))
-                .filter(|streak| *streak == target)
-                .count()
-        })
-        .sum()
+    world.find_word::<Dir>(&['X', 'M', 'A', 'S']).len()
 }
 
 fn part2(world: GridCharWorld) -> usize {
-    let target = vec!['M', 'A', 'S'];
-    let diags = vec![Dir::Nw, Dir::Sw, Dir::Ne, Dir::Se];
+    let x_mas = "M.S\n.A.\nM.S".parse::<GridCharWorld>().unwrap();
     world
-        .position_value_iter()
-        .filter(|(p, c)| **c == 'A' && !world.at_edge(**p))
-        .filter(|(p, _)| {
-            diags
-                .iter()
-                .filter(|d| world.values_from(d.neighbor(**p), d.inverse(), target.len()) == target)
-                .count()
-                == 2
-        })
-        .count()
+        .find_pattern(&x_mas, Some('.'), Orientation::rotations())
+        .len()
 }
@@ -1,17 +1,15 @@
-use std::{cmp::Ordering, collections::BTreeSet};
-
-use advent2024::{advent_main, all_lines, Part};
+use advent2024::{advent_main, all_lines, graph::PrecedenceRules, Part};
 
 fn main() -> anyhow::Result<()> {
     advent_main(|filename, part, _| {
         let mut lines = all_lines(filename)?;
-        let pairs = collect_rules_from(&mut lines);
-        println!("{}", add_up_medians(part, &pairs, lines));
+        let rules = collect_rules_from(&mut lines);
+        println!("{}", add_up_medians(part, &rules, lines));
         Ok(())
     })
 }
 
-fn collect_rules_from(lines: &mut impl Iterator<Item = String>) -> BTreeSet<(i64, i64)> {
+fn collect_rules_from(lines: &mut impl Iterator<Item = String>) -> PrecedenceRules<i64> {
     lines
         .by_ref()
         .take_while(|s| s.len() > 0)
@@ -24,35 +22,20 @@ fn collect_rules_from(lines: &mut impl Iterator<Item = String>) -> BTreeSet<(i64
 
 fn add_up_medians(
     part: Part,
-    pairs: &BTreeSet<(i64, i64)>,
+    rules: &PrecedenceRules<i64>,
     lines: impl Iterator<Item = String>,
 ) -> i64 {
     let mut count = 0;
     for line in lines {
-        let mut update = line.split(",").map(|n| n.parse().unwrap()).collect();
-        if (part == Part::One) == passes_ordering_rule(&update, pairs) {
+        let mut update: Vec<i64> = line.split(",").map(|n| n.parse().unwrap()).collect();
+        if (part == Part::One) == rules.is_satisfied_by(&update) {
             if part == Part::Two {
-                update.sort_unstable_by(|a, b| {
-                    if pairs.contains(&(*a, *b)) {
-                        Ordering::Less
-                    } else {
-                        Ordering::Greater
-                    }
-                });
+                update = rules
+                    .order(update.iter().copied())
+                    .unwrap_or_else(|conflicts| panic!("Conflicting rules: {conflicts:?}"));
             }
             count += update[update.len() / 2];
         }
     }
     count
 }
-
-fn passes_ordering_rule(update: &Vec<i64>, pairs: &BTreeSet<(i64, i64)>) -> bool {
-    for i in 0..update.len() {
-        for j in (i + 1)..update.len() {
-            if pairs.contains(&(update[j], update[i])) {
-                return false;
-            }
-        }
-    }
-    true
-}
@@ -2,8 +2,10 @@ use std::collections::HashSet;
 
 use advent2024::{
     advent_main,
+    cycles::brent_iter,
     grid::GridCharWorld,
     multidim::{DirType, ManhattanDir, Position},
+    parallel::parallel_count,
     Part,
 };
 
@@ -28,30 +30,21 @@ fn part1(patrol_map: &GridCharWorld) -> usize {
 }
 
 fn part2(patrol_map: &GridCharWorld) -> usize {
-    let mut alternate_world = patrol_map.clone();
-    let mut cyclic_barriers = HashSet::new();
     let start = Guard::new(patrol_map);
-    for pose in start.travel_to_exit(patrol_map).skip(1) {
-        if !cyclic_barriers.contains(&pose.at) {
-            alternate_world.update(pose.at, '#');
-            if has_cycle(&alternate_world, start) {
-                cyclic_barriers.insert(pose.at);
-            }
-            alternate_world.update(pose.at, '.');
-        }
-    }
-    cyclic_barriers.len()
+    let candidates = start
+        .travel_to_exit(patrol_map)
+        .skip(1)
+        .map(|pose| pose.at)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    parallel_count(&candidates, |barrier| {
+        has_cycle(patrol_map, start, *barrier)
+    })
 }
 
-fn has_cycle(patrol_map: &GridCharWorld, guard: Guard) -> bool {
-    let mut visited = HashSet::new();
-    for g in guard.travel_to_exit(patrol_map) {
-        if visited.contains(&g) {
-            return true;
-        }
-        visited.insert(g);
-    }
-    false
+fn has_cycle(patrol_map: &GridCharWorld, guard: Guard, barrier: Position) -> bool {
+    brent_iter(guard.travel_to_exit(patrol_map).with_barrier(barrier)).is_some()
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
@@ -76,6 +69,7 @@ impl Guard {
         GuardTravelIterator {
             guard: Some(*self),
             patrol_map,
+            barrier: None,
         }
     }
 
@@ -94,9 +88,20 @@ impl Guard {
     }
 }
 
+// The map is shared between threads, so an extra barrier is kept here rather
+// than written into a copy of the map.
+#[derive(Clone)]
 struct GuardTravelIterator<'a> {
     guard: Option<Guard>,
     patrol_map: &'a GridCharWorld,
+    barrier: Option<Position>,
+}
+
+impl<'a> GuardTravelIterator<'a> {
+    fn with_barrier(mut self, barrier: Position) -> Self {
+        self.barrier = Some(barrier);
+        self
+    }
 }
 
 impl<'a> Iterator for GuardTravelIterator<'a> {
@@ -106,9 +111,12 @@ impl<'a> Iterator for GuardTravelIterator<'a> {
         let prev = self.guard;
         self.guard = self.guard.and_then(|g| {
             let ahead = g.facing.neighbor(g.at);
-            self.patrol_map.value(ahead).map(|c| match c {
-                '#' => g.turn(),
-                _ => g.go(ahead),
+            self.patrol_map.value(ahead).map(|c| {
+                if c == '#' || self.barrier == Some(ahead) {
+                    g.turn()
+                } else {
+                    g.go(ahead)
+                }
             })
         });
         prev
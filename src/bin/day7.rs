@@ -1,4 +1,5 @@
-use advent2024::{advent_main, all_lines, combinations::ComboIterator, Part};
+use advent2024::{advent_main, all_lines, Part};
+use itertools::{repeat_n, Itertools};
 
 const PART_1: [Op; 2] = [Op::Plus, Op::Times];
 const PART_2: [Op; 3] = [Op::Plus, Op::Times, Op::Concat];
@@ -56,7 +57,9 @@ fn solve_iterator(
     target: i64,
     nums: &Vec<i64>,
 ) -> Option<Vec<Op>> {
-    ComboIterator::new(iter, nums.len() - 1).find(|combo| Op::apply(combo, nums) == target)
+    repeat_n(iter, nums.len() - 1)
+        .multi_cartesian_product()
+        .find(|combo| Op::apply(combo, nums) == target)
 }
 
 #[derive(Copy, Clone, Debug)]
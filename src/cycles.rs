@@ -0,0 +1,175 @@
+// Cycle detection in sequences of states using constant memory. Each function
+// returns (prefix length, cycle length): the sequence first repeats after
+// `prefix` states, and from then on repeats every `cycle length` states.
+
+// Brent's algorithm over a sequence that may end. Returns None if it ends
+// before the repeat is confirmed, which can take up to a further cycle length
+// of states. The iterator is cloned to restart the sequence.
+pub fn brent_iter<T: PartialEq + Clone, I: Iterator<Item = T> + Clone>(
+    iter: I,
+) -> Option<(usize, usize)> {
+    let mut ahead = iter.clone();
+    let mut tortoise = ahead.next()?;
+    let mut hare = ahead.next()?;
+    let mut power = 1;
+    let mut period = 1;
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = ahead.next()?;
+        period += 1;
+    }
+    let mut behind = iter.clone();
+    let mut ahead = iter;
+    let mut tortoise = behind.next()?;
+    let mut hare = ahead.nth(period)?;
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = behind.next()?;
+        hare = ahead.next()?;
+        prefix += 1;
+    }
+    Some((prefix, period))
+}
+
+// Floyd's tortoise and hare over a sequence that may end. Its hare runs twice
+// as far ahead as Brent's, so it needs a longer sequence to confirm a repeat.
+pub fn floyd_iter<T: PartialEq + Clone, I: Iterator<Item = T> + Clone>(
+    iter: I,
+) -> Option<(usize, usize)> {
+    let mut slow = iter.clone();
+    let mut fast = iter.clone();
+    slow.next()?;
+    fast.next()?;
+    let mut meeting = loop {
+        let tortoise = slow.next()?;
+        fast.next()?;
+        if tortoise == fast.next()? {
+            break tortoise;
+        }
+    };
+    // `slow` sits one past the meeting point, which is a whole number of
+    // cycles ahead of the start.
+    let mut from_start = iter;
+    let mut tortoise = from_start.next()?;
+    let mut prefix = 0;
+    while tortoise != meeting {
+        tortoise = from_start.next()?;
+        meeting = slow.next()?;
+        prefix += 1;
+    }
+    let mut period = 1;
+    while slow.next()? != meeting {
+        period += 1;
+    }
+    Some((prefix, period))
+}
+
+// Brent's algorithm for the endless sequence start, step(start), ...
+pub fn brent<T: PartialEq + Clone, F: Fn(&T) -> T>(start: &T, step: F) -> (usize, usize) {
+    brent_iter(Steps::new(start.clone(), &step)).unwrap()
+}
+
+// Floyd's algorithm for the endless sequence start, step(start), ...
+pub fn floyd<T: PartialEq + Clone, F: Fn(&T) -> T>(start: &T, step: F) -> (usize, usize) {
+    floyd_iter(Steps::new(start.clone(), &step)).unwrap()
+}
+
+// The state after `n` steps from `start`, taking at most one pass through the
+// prefix and cycle no matter how large `n` is.
+pub fn nth_state<T: PartialEq + Clone, F: Fn(&T) -> T>(start: &T, step: F, n: usize) -> T {
+    let (prefix, period) = brent(start, &step);
+    let n = if n < prefix {
+        n
+    } else {
+        prefix + (n - prefix) % period
+    };
+    Steps::new(start.clone(), &step).nth(n).unwrap()
+}
+
+struct Steps<'a, T, F> {
+    next: T,
+    step: &'a F,
+}
+
+impl<'a, T, F: Fn(&T) -> T> Steps<'a, T, F> {
+    fn new(start: T, step: &'a F) -> Self {
+        Self { next: start, step }
+    }
+}
+
+impl<T: Clone, F> Clone for Steps<'_, T, F> {
+    fn clone(&self) -> Self {
+        Self {
+            next: self.next.clone(),
+            step: self.step,
+        }
+    }
+}
+
+impl<T, F: Fn(&T) -> T> Iterator for Steps<'_, T, F> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let following = (self.step)(&self.next);
+        Some(std::mem::replace(&mut self.next, following))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brent, brent_iter, floyd, floyd_iter, nth_state};
+
+    // Compares against the first repeat found by remembering every state.
+    fn brute_force(start: u64, step: impl Fn(&u64) -> u64) -> (usize, usize) {
+        let mut seen = std::collections::HashMap::new();
+        let mut state = start;
+        for i in 0.. {
+            if let Some(first) = seen.insert(state, i) {
+                return (first, i - first);
+            }
+            state = step(&state);
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_modular_sequences() {
+        for modulus in 1..60 {
+            for multiplier in 0..10 {
+                for start in [0, 1, 7] {
+                    let step = |x: &u64| (x * multiplier + 3) % modulus;
+                    let expected = brute_force(start, step);
+                    assert_eq!(brent(&start, step), expected);
+                    assert_eq!(floyd(&start, step), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_iterators() {
+        let repeating = [5, 6, 1, 2, 3, 1, 2, 3];
+        let cycled = repeating[..2].iter().chain(repeating[2..5].iter().cycle());
+        assert_eq!(brent_iter(cycled.clone()), Some((2, 3)));
+        assert_eq!(floyd_iter(cycled), Some((2, 3)));
+        assert_eq!(brent_iter(repeating.iter()), Some((2, 3)));
+        assert_eq!(brent_iter(0..100), None);
+        assert_eq!(floyd_iter(0..100), None);
+        assert_eq!(brent_iter(std::iter::empty::<u8>()), None);
+    }
+
+    #[test]
+    fn test_nth_state() {
+        // 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1 -> 4 -> ...
+        let collatz = |n: &u64| if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+        assert_eq!(brent(&10, collatz), (4, 3));
+        assert_eq!(nth_state(&10, collatz, 0), 10);
+        assert_eq!(nth_state(&10, collatz, 3), 8);
+        assert_eq!(nth_state(&10, collatz, 1_000_000_000_000), 4);
+        assert_eq!(nth_state(&10, collatz, 1_000_000_000_001), 2);
+    }
+}
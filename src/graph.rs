@@ -1,27 +1,44 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
-    fmt::Display,
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::{Debug, Display},
     iter::repeat,
     hash::Hash,
+    ops::Sub,
+    str::FromStr,
 };
 
-use common_macros::b_tree_set;
-use hash_histogram::HashHistogram;
-use itertools::Itertools;
+use anyhow::anyhow;
+
+use common_macros::{b_tree_set, hash_map};
+use itertools::{repeat_n, Itertools};
 use trait_set::trait_set;
 
 use std::io::Write;
 
-use crate::search_iter::BfsIter;
+use crate::search_iter::{path_back_from, BfsIter, Estimator, PrioritySearchIter};
+
+trait_set! {pub trait GraphNode = Clone + Ord + Hash + Debug}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdjacencySets<N: GraphNode = String> {
+    graph: BTreeMap<N, BTreeSet<N>>,
+    // Edges whose reverse is absent; self-loops count as unmatched.
+    unmatched_edges: usize,
+}
 
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
-pub struct AdjacencySets {
-    graph: BTreeMap<String, BTreeSet<String>>,
+impl<N: GraphNode> Default for AdjacencySets<N> {
+    fn default() -> Self {
+        Self {
+            graph: BTreeMap::new(),
+            unmatched_edges: 0,
+        }
+    }
 }
 
-impl AdjacencySets {
-    pub fn keys(&self) -> impl Iterator<Item = &str> {
-        self.graph.keys().map(|s| s.as_str())
+impl<N: GraphNode> AdjacencySets<N> {
+    pub fn keys(&self) -> impl Iterator<Item = &N> {
+        self.graph.keys()
     }
 
     pub fn len(&self) -> usize {
@@ -40,122 +57,1317 @@ impl AdjacencySets {
     }
 
     pub fn is_directed(&self) -> bool {
-        self.num_symmetric_edges() * 2 != self.num_edges()
+        self.unmatched_edges > 0
     }
 
-    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+    pub fn pairs(&self) -> impl Iterator<Item = (&N, &N)> {
         // Inspired by: https://stackoverflow.com/a/78248495/906268
-        self.graph.keys().flat_map(|k| {
-            repeat(k.as_str()).zip(
+        self.graph
+            .iter()
+            .flat_map(|(k, neighbors)| repeat(k).zip(neighbors.iter()))
+    }
+
+    pub fn neighbors_of<Q: ?Sized + Ord>(&self, node: &Q) -> impl Iterator<Item = &N>
+    where
+        N: Borrow<Q>,
+    {
+        self.graph.get(node).unwrap().iter()
+    }
+
+    pub fn are_connected<Q: ?Sized + Ord>(&self, start: &Q, end: &Q) -> bool
+    where
+        N: Borrow<Q>,
+    {
+        self.graph.get(start).map_or(false, |set| set.contains(end))
+    }
+
+    pub fn connect2<Q: ?Sized + Ord + ToOwned<Owned = N>>(&mut self, start: &Q, end: &Q)
+    where
+        N: Borrow<Q>,
+    {
+        self.connect(start, end);
+        self.connect(end, start);
+    }
+
+    pub fn connect<Q: ?Sized + Ord + ToOwned<Owned = N>>(&mut self, start: &Q, end: &Q)
+    where
+        N: Borrow<Q>,
+    {
+        let reverse_present = start != end && self.are_connected(end, start);
+        let added = match self.graph.get_mut(start) {
+            None => {
                 self.graph
-                    .get(k.as_str())
-                    .unwrap()
+                    .insert(start.to_owned(), b_tree_set! {end.to_owned()});
+                true
+            }
+            Some(connections) => connections.insert(end.to_owned()),
+        };
+        if added {
+            if reverse_present {
+                self.unmatched_edges -= 1;
+            } else {
+                self.unmatched_edges += 1;
+            }
+        }
+        if !self.graph.contains_key(end) {
+            self.graph.insert(end.to_owned(), BTreeSet::new());
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.graph.entry(node).or_default();
+    }
+
+    // Keeps only the given nodes and the edges among them. Nodes that were
+    // absent from the original graph are added without edges.
+    pub fn induced_subgraph<I: IntoIterator<Item = N>>(&self, nodes: I) -> Self {
+        let nodes = nodes.into_iter().collect::<BTreeSet<_>>();
+        let mut result = Self::default();
+        for n in nodes.iter() {
+            result.add_node(n.clone());
+            if let Some(neighbors) = self.graph.get(n) {
+                for neighbor in neighbors.intersection(&nodes) {
+                    result.connect(n, neighbor);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn in_degrees(&self) -> HashMap<N, usize> {
+        self.degrees(self.pairs().map(|(_, dest)| dest))
+    }
+
+    pub fn out_degrees(&self) -> HashMap<N, usize> {
+        self.degrees(self.pairs().map(|(src, _)| src))
+    }
+
+    fn degrees<'a, I: Iterator<Item = &'a N>>(&'a self, endpoints: I) -> HashMap<N, usize> {
+        let mut result = self
+            .graph
+            .keys()
+            .map(|v| (v.clone(), 0))
+            .collect::<HashMap<_, _>>();
+        for v in endpoints {
+            *result.get_mut(v).unwrap() += 1;
+        }
+        result
+    }
+
+    // Kahn's Algorithm
+    // Returns None if the graph contains a cycle.
+    pub fn topologial_ordering(&self) -> Option<Vec<N>> {
+        let mut in_degrees = self.in_degrees();
+        let source_nodes = in_degrees
+            .iter()
+            .filter(|(_, c)| **c == 0)
+            .map(|(n, _)| n.clone())
+            .collect_vec();
+        let visited = BfsIter::multi_start(source_nodes.iter().cloned(), |n| {
+            let mut next = vec![];
+            for neighbor in self.neighbors_of(n) {
+                let count = in_degrees.get_mut(neighbor).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    next.push(neighbor.clone());
+                }
+            }
+            next
+        })
+        .collect_vec();
+        if visited.len() == self.len() {
+            Some(visited)
+        } else {
+            None
+        }
+    }
+
+    // The clique routines below treat the graph as undirected: an edge in
+    // either direction joins two nodes, and self-loops are ignored.
+
+    // Bron-Kerbosch with pivoting.
+    pub fn maximal_cliques(&self) -> Vec<BTreeSet<&N>> {
+        let neighbors = self.undirected_neighbors();
+        let mut result = vec![];
+        bron_kerbosch(
+            &neighbors,
+            BTreeSet::new(),
+            self.keys().collect(),
+            BTreeSet::new(),
+            &mut result,
+        );
+        result
+    }
+
+    pub fn maximum_clique(&self) -> BTreeSet<&N> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(|c| c.len())
+            .unwrap_or_default()
+    }
+
+    // Every clique of exactly `k` nodes, maximal or not.
+    pub fn k_cliques(&self, k: usize) -> Vec<BTreeSet<&N>> {
+        let neighbors = self.undirected_neighbors();
+        let mut result = vec![];
+        if k > 0 {
+            for (node, adjacent) in neighbors.iter() {
+                let candidates = adjacent.iter().filter(|n| *n > node).copied().collect();
+                extend_clique(&neighbors, b_tree_set! {*node}, candidates, k, &mut result);
+            }
+        }
+        result
+    }
+
+    pub fn count_k_cliques(&self, k: usize) -> usize {
+        self.k_cliques(k).len()
+    }
+
+    pub fn triangles(&self) -> Vec<BTreeSet<&N>> {
+        self.k_cliques(3)
+    }
+
+    // The cycle routines below treat every edge as directed, so each
+    // symmetric pair forms a cycle of length two.
+
+    // Tarjan's algorithm. Components are listed in reverse topological order.
+    pub fn strongly_connected_components(&self) -> Vec<BTreeSet<N>> {
+        let mut state = TarjanState::default();
+        for node in self.keys() {
+            if !state.indices.contains_key(node) {
+                self.tarjan(node, &mut state);
+            }
+        }
+        state.components
+    }
+
+    fn tarjan<'a>(&'a self, node: &'a N, state: &mut TarjanState<'a, N>) {
+        let index = state.indices.len();
+        state.indices.insert(node, index);
+        state.low_links.insert(node, index);
+        state.stack.push(node);
+        state.on_stack.insert(node);
+        for neighbor in self.neighbors_of(node) {
+            if !state.indices.contains_key(neighbor) {
+                self.tarjan(neighbor, state);
+                let low = state.low_links[node].min(state.low_links[neighbor]);
+                state.low_links.insert(node, low);
+            } else if state.on_stack.contains(neighbor) {
+                let low = state.low_links[node].min(state.indices[neighbor]);
+                state.low_links.insert(node, low);
+            }
+        }
+        if state.low_links[node] == index {
+            let mut component = BTreeSet::new();
+            loop {
+                let member = state.stack.pop().unwrap();
+                state.on_stack.remove(member);
+                component.insert(member.clone());
+                if member == node {
+                    break;
+                }
+            }
+            state.components.push(component);
+        }
+    }
+
+    // Each strongly connected component becomes a single node, identified by
+    // its index in the returned component list. The resulting graph is a DAG.
+    pub fn condensation(&self) -> (Vec<BTreeSet<N>>, AdjacencySets<usize>) {
+        let components = self.strongly_connected_components();
+        let component_of = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| c.iter().map(move |n| (n, i)))
+            .collect::<HashMap<_, _>>();
+        let mut dag = AdjacencySets::default();
+        for i in 0..components.len() {
+            dag.add_node(i);
+        }
+        for (a, b) in self.pairs() {
+            let (ca, cb) = (component_of[a], component_of[b]);
+            if ca != cb {
+                dag.connect(&ca, &cb);
+            }
+        }
+        (components, dag)
+    }
+
+    pub fn is_dag(&self) -> bool {
+        self.find_cycle().is_none()
+    }
+
+    // Returns the nodes of one cycle in edge order; the last node connects
+    // back to the first.
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        let mut finished = BTreeSet::new();
+        for node in self.keys() {
+            if !finished.contains(node) {
+                let mut path = vec![];
+                if let Some(cycle) = self.cycle_from(node, &mut path, &mut finished) {
+                    return Some(cycle);
+                }
+            }
+        }
+        None
+    }
+
+    fn cycle_from<'a>(
+        &'a self,
+        node: &'a N,
+        path: &mut Vec<&'a N>,
+        finished: &mut BTreeSet<&'a N>,
+    ) -> Option<Vec<N>> {
+        path.push(node);
+        for neighbor in self.neighbors_of(node) {
+            if let Some(start) = path.iter().position(|p| *p == neighbor) {
+                return Some(path[start..].iter().map(|n| (*n).clone()).collect());
+            } else if !finished.contains(neighbor) {
+                if let Some(cycle) = self.cycle_from(neighbor, path, finished) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        finished.insert(node);
+        None
+    }
+
+    // Every elementary cycle, each starting from its smallest node.
+    // The count can grow exponentially with the size of the graph.
+    pub fn elementary_cycles(&self) -> Vec<Vec<N>> {
+        let mut result = vec![];
+        for component in self.strongly_connected_components() {
+            for start in component.iter() {
+                let mut path = vec![start];
+                self.cycles_through(start, start, &component, &mut path, &mut result);
+            }
+        }
+        result
+    }
+
+    fn cycles_through<'a>(
+        &'a self,
+        start: &'a N,
+        node: &'a N,
+        component: &'a BTreeSet<N>,
+        path: &mut Vec<&'a N>,
+        result: &mut Vec<Vec<N>>,
+    ) {
+        for neighbor in self.neighbors_of(node) {
+            if neighbor == start {
+                result.push(path.iter().map(|n| (*n).clone()).collect());
+            } else if neighbor > start && component.contains(neighbor) && !path.contains(&neighbor)
+            {
+                path.push(neighbor);
+                self.cycles_through(start, neighbor, component, path, result);
+                path.pop();
+            }
+        }
+    }
+
+    // The connectivity routines below ignore edge direction, so in a directed
+    // graph they describe the underlying undirected graph. Connected components
+    // are then the weakly connected components.
+    fn undirected_neighbors(&self) -> BTreeMap<&N, BTreeSet<&N>> {
+        let mut result = self
+            .keys()
+            .map(|k| (k, BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+        for (a, b) in self.pairs().filter(|(a, b)| a != b) {
+            result.get_mut(a).unwrap().insert(b);
+            result.get_mut(b).unwrap().insert(a);
+        }
+        result
+    }
+
+    pub fn connected_components(&self) -> Vec<BTreeSet<N>> {
+        let neighbors = self.undirected_neighbors();
+        let mut seen = BTreeSet::new();
+        let mut result = vec![];
+        for node in self.keys() {
+            if !seen.contains(node) {
+                let component = BfsIter::new(node, |n| neighbors[n].iter().copied().collect())
+                    .collect::<BTreeSet<_>>();
+                seen.extend(component.iter().copied());
+                result.push(component.into_iter().cloned().collect());
+            }
+        }
+        result
+    }
+
+    // Stoer-Wagner with every edge weighing one. Returns the number of edges
+    // in a smallest cut and the nodes on one side of it.
+    pub fn global_min_cut(&self) -> Option<(usize, BTreeSet<N>)> {
+        let mut weighted = WeightedGraph::default();
+        for node in self.keys() {
+            weighted.add_node(node.clone());
+        }
+        for (a, b) in self.pairs().filter(|(a, b)| a != b) {
+            weighted.connect2(a.clone(), b.clone(), 1);
+        }
+        weighted.global_min_cut()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    // Each bridge is reported once, with its smaller endpoint first.
+    pub fn bridges(&self) -> BTreeSet<(N, N)> {
+        self.biconnectivity().bridges
+    }
+
+    pub fn articulation_points(&self) -> BTreeSet<N> {
+        self.biconnectivity().articulation_points
+    }
+
+    // Node sets of the maximal 2-connected subgraphs. A bridge forms a
+    // component of its own, and isolated nodes belong to none.
+    pub fn biconnected_components(&self) -> Vec<BTreeSet<N>> {
+        self.biconnectivity().components
+    }
+
+    fn biconnectivity(&self) -> Biconnectivity<'_, N> {
+        let mut state = Biconnectivity {
+            neighbors: self.undirected_neighbors(),
+            discovered: HashMap::new(),
+            low: HashMap::new(),
+            edge_stack: vec![],
+            bridges: BTreeSet::new(),
+            articulation_points: BTreeSet::new(),
+            components: vec![],
+        };
+        for node in self.keys() {
+            if !state.discovered.contains_key(node) {
+                state.visit(node, None);
+            }
+        }
+        state
+    }
+}
+
+struct Biconnectivity<'a, N: GraphNode> {
+    neighbors: BTreeMap<&'a N, BTreeSet<&'a N>>,
+    discovered: HashMap<&'a N, usize>,
+    low: HashMap<&'a N, usize>,
+    edge_stack: Vec<(&'a N, &'a N)>,
+    bridges: BTreeSet<(N, N)>,
+    articulation_points: BTreeSet<N>,
+    components: Vec<BTreeSet<N>>,
+}
+
+impl<'a, N: GraphNode> Biconnectivity<'a, N> {
+    fn visit(&mut self, node: &'a N, parent: Option<&'a N>) {
+        let order = self.discovered.len();
+        self.discovered.insert(node, order);
+        self.low.insert(node, order);
+        let mut children = 0;
+        for neighbor in self.neighbors[node].clone() {
+            if Some(neighbor) == parent {
+                continue;
+            }
+            match self.discovered.get(neighbor).copied() {
+                None => {
+                    children += 1;
+                    self.edge_stack.push((node, neighbor));
+                    self.visit(neighbor, Some(node));
+                    let neighbor_low = self.low[neighbor];
+                    self.low.insert(node, self.low[node].min(neighbor_low));
+                    if neighbor_low > order {
+                        let bridge = if node < neighbor {
+                            (node.clone(), neighbor.clone())
+                        } else {
+                            (neighbor.clone(), node.clone())
+                        };
+                        self.bridges.insert(bridge);
+                    }
+                    if neighbor_low >= order {
+                        if parent.is_some() {
+                            self.articulation_points.insert(node.clone());
+                        }
+                        self.pop_component(node, neighbor);
+                    }
+                }
+                Some(neighbor_order) => {
+                    if neighbor_order < order {
+                        self.edge_stack.push((node, neighbor));
+                    }
+                    self.low.insert(node, self.low[node].min(neighbor_order));
+                }
+            }
+        }
+        if parent.is_none() && children > 1 {
+            self.articulation_points.insert(node.clone());
+        }
+    }
+
+    fn pop_component(&mut self, node: &'a N, child: &'a N) {
+        let mut component = BTreeSet::new();
+        while let Some((a, b)) = self.edge_stack.pop() {
+            component.insert(a.clone());
+            component.insert(b.clone());
+            if a == node && b == child {
+                break;
+            }
+        }
+        self.components.push(component);
+    }
+}
+
+impl<N: GraphNode + FromStr> AdjacencySets<N>
+where
+    <N as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+{
+    // One `a<separator>b` edge per line, such as `a-b` or `a->b`. Blank lines
+    // are skipped. Symmetric edge lists connect both ways.
+    pub fn from_edge_list(text: &str, separator: &str, symmetric: bool) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        for line in text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+        {
+            let (start, end) = line
+                .split_once(separator)
+                .ok_or_else(|| anyhow!("No '{separator}' in '{line}'"))?;
+            let (start, end) = (start.trim().parse::<N>()?, end.trim().parse::<N>()?);
+            if symmetric {
+                result.connect2(&start, &end);
+            } else {
+                result.connect(&start, &end);
+            }
+        }
+        Ok(result)
+    }
+
+    // One `node: n1 n2 ...` line per node, with an edge from `node` to each `n`.
+    pub fn from_adjacency_lines(text: &str) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        for line in text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+        {
+            let (node, neighbors) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("No ':' in '{line}'"))?;
+            let node = node.trim().parse::<N>()?;
+            result.add_node(node.clone());
+            for neighbor in neighbors.split_whitespace() {
+                result.connect(&node, &neighbor.parse::<N>()?);
+            }
+        }
+        Ok(result)
+    }
+
+    // Reads node and edge statements, including edge chains and `{ ... }`
+    // operands such as `a -> {b c}`, from `graph` and `digraph` descriptions.
+    // Attributes, graph settings and subgraph boundaries are ignored. Ports,
+    // HTML labels and other unsupported syntax produce an error.
+    pub fn from_dot(text: &str) -> anyhow::Result<Self> {
+        let mut parser = DotParser {
+            tokens: dot_tokens(text)?,
+            next: 0,
+            directed: false,
+            graph: Self::default(),
+        };
+        if parser.peek() == Some(&DotToken::Keyword("strict".to_string())) {
+            parser.next += 1;
+        }
+        parser.directed = match parser.advance() {
+            Some(DotToken::Keyword(header)) if header == "digraph" => true,
+            Some(DotToken::Keyword(header)) if header == "graph" => false,
+            _ => return Err(anyhow!("Expected 'graph' or 'digraph'")),
+        };
+        if let Some(DotToken::Id(_)) = parser.peek() {
+            parser.next += 1;
+        }
+        parser.expect(DotToken::Open)?;
+        parser.statements()?;
+        match parser.advance() {
+            None => Ok(parser.graph),
+            Some(token) => Err(anyhow!("Unexpected {token:?} after the closing brace")),
+        }
+    }
+}
+
+impl<N: GraphNode + Display> AdjacencySets<N> {
+    pub fn to_edge_list(&self, separator: &str) -> String {
+        self.pairs()
+            .map(|(a, b)| format!("{a}{separator}{b}\n"))
+            .collect()
+    }
+
+    pub fn to_adjacency_lines(&self) -> String {
+        self.graph
+            .iter()
+            .map(|(node, neighbors)| format!("{node}: {}\n", neighbors.iter().join(" ")))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum DotToken {
+    Id(String),
+    Keyword(String),
+    Edge { directed: bool },
+    Open,
+    Close,
+    Equals,
+    End,
+}
+
+const DOT_KEYWORDS: [&str; 6] = ["strict", "graph", "digraph", "subgraph", "node", "edge"];
+
+// Newlines are ordinary whitespace. Comments, `#` preprocessor lines and
+// bracketed attribute lists are dropped.
+fn dot_tokens(text: &str) -> anyhow::Result<Vec<DotToken>> {
+    let mut result = vec![];
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '#' if line_start => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                loop {
+                    match chars.next() {
+                        None => return Err(anyhow!("Unclosed comment")),
+                        Some('/') if prev == '*' => break,
+                        Some(c) => prev = c,
+                    }
+                }
+            }
+            ';' => result.push(DotToken::End),
+            '{' => result.push(DotToken::Open),
+            '}' => result.push(DotToken::Close),
+            '=' => result.push(DotToken::Equals),
+            '[' => {
+                let mut in_quotes = false;
+                loop {
+                    match chars.next() {
+                        None => return Err(anyhow!("Unclosed '['")),
+                        Some('\\') if in_quotes => {
+                            chars.next();
+                        }
+                        Some('"') => in_quotes = !in_quotes,
+                        Some(']') if !in_quotes => break,
+                        _ => {}
+                    }
+                }
+            }
+            '-' if matches!(chars.peek(), Some('-') | Some('>')) => {
+                let directed = chars.next() == Some('>');
+                result.push(DotToken::Edge { directed });
+            }
+            '"' => {
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(anyhow!("Unclosed quote")),
+                        Some('\\') => id.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => id.push(c),
+                    }
+                }
+                result.push(DotToken::Id(id));
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = c.to_string();
+                while let Some(next) = chars.peek() {
+                    if next.is_alphanumeric() || *next == '_' || *next == '.' {
+                        id.push(chars.next().unwrap());
+                    } else {
+                        break;
+                    }
+                }
+                let lower = id.to_lowercase();
+                if DOT_KEYWORDS.contains(&lower.as_str()) {
+                    result.push(DotToken::Keyword(lower));
+                } else {
+                    result.push(DotToken::Id(id));
+                }
+            }
+            c => return Err(anyhow!("Unsupported DOT syntax at '{c}'")),
+        }
+        line_start = c == '\n' || (line_start && c.is_whitespace());
+    }
+    Ok(result)
+}
+
+struct DotParser<N: GraphNode> {
+    tokens: Vec<DotToken>,
+    next: usize,
+    directed: bool,
+    graph: AdjacencySets<N>,
+}
+
+impl<N: GraphNode + FromStr> DotParser<N>
+where
+    <N as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+{
+    fn peek(&self) -> Option<&DotToken> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<DotToken> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    fn expect(&mut self, token: DotToken) -> anyhow::Result<()> {
+        match self.advance() {
+            Some(t) if t == token => Ok(()),
+            other => Err(anyhow!("Expected {token:?}, found {other:?}")),
+        }
+    }
+
+    // Parses up to and including the closing brace, returning every node
+    // mentioned along the way.
+    fn statements(&mut self) -> anyhow::Result<BTreeSet<N>> {
+        let mut nodes = BTreeSet::new();
+        loop {
+            match self.peek() {
+                None => return Err(anyhow!("Unclosed '{{'")),
+                Some(DotToken::Close) => {
+                    self.next += 1;
+                    return Ok(nodes);
+                }
+                Some(DotToken::End) => self.next += 1,
+                _ => nodes.extend(self.statement()?),
+            }
+        }
+    }
+
+    fn statement(&mut self) -> anyhow::Result<BTreeSet<N>> {
+        match (self.peek(), self.tokens.get(self.next + 1)) {
+            (Some(DotToken::Keyword(k)), _) if ["graph", "node", "edge"].contains(&k.as_str()) => {
+                self.next += 1;
+                return Ok(BTreeSet::new());
+            }
+            (Some(DotToken::Id(_)), Some(DotToken::Equals)) => {
+                self.next += 2;
+                return match self.advance() {
+                    Some(DotToken::Id(_)) => Ok(BTreeSet::new()),
+                    other => Err(anyhow!("Expected a value after '=', found {other:?}")),
+                };
+            }
+            _ => {}
+        }
+        let mut operand = self.operand()?;
+        let mut nodes = operand.clone();
+        while let Some(DotToken::Edge { directed }) = self.peek() {
+            if *directed != self.directed {
+                return Err(anyhow!("Edge operator does not match the graph type"));
+            }
+            self.next += 1;
+            let next = self.operand()?;
+            for (start, end) in operand.iter().cartesian_product(next.iter()) {
+                if self.directed {
+                    self.graph.connect(start, end);
+                } else {
+                    self.graph.connect2(start, end);
+                }
+            }
+            nodes.extend(next.iter().cloned());
+            operand = next;
+        }
+        Ok(nodes)
+    }
+
+    fn operand(&mut self) -> anyhow::Result<BTreeSet<N>> {
+        match self.advance() {
+            Some(DotToken::Id(id)) => {
+                let node = id.parse::<N>()?;
+                self.graph.add_node(node.clone());
+                Ok(b_tree_set! {node})
+            }
+            Some(DotToken::Keyword(k)) if k == "subgraph" => {
+                if let Some(DotToken::Id(_)) = self.peek() {
+                    self.next += 1;
+                }
+                self.expect(DotToken::Open)?;
+                self.statements()
+            }
+            Some(DotToken::Open) => self.statements(),
+            other => Err(anyhow!("Unexpected {other:?}")),
+        }
+    }
+}
+
+// Rules of the form "a must come before b".
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PrecedenceRules<N: GraphNode> {
+    rules: AdjacencySets<N>,
+}
+
+impl<N: GraphNode> Default for PrecedenceRules<N> {
+    fn default() -> Self {
+        Self {
+            rules: AdjacencySets::default(),
+        }
+    }
+}
+
+impl<N: GraphNode> FromIterator<(N, N)> for PrecedenceRules<N> {
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(iter: T) -> Self {
+        let mut result = Self::default();
+        for (before, after) in iter {
+            result.add_rule(before, after);
+        }
+        result
+    }
+}
+
+impl<N: GraphNode> PrecedenceRules<N> {
+    pub fn add_rule(&mut self, before: N, after: N) {
+        self.rules.connect(&before, &after);
+    }
+
+    pub fn requires(&self, before: &N, after: &N) -> bool {
+        self.rules.are_connected(before, after)
+    }
+
+    pub fn restricted_to<I: IntoIterator<Item = N>>(&self, items: I) -> AdjacencySets<N> {
+        self.rules.induced_subgraph(items)
+    }
+
+    pub fn is_satisfied_by(&self, sequence: &[N]) -> bool {
+        (0..sequence.len())
+            .all(|i| ((i + 1)..sequence.len()).all(|j| !self.requires(&sequence[j], &sequence[i])))
+    }
+
+    // Orders `items` so that every applicable rule holds; repeated items are
+    // kept and placed next to each other. When that is impossible, returns a
+    // set of rules that contradict each other.
+    pub fn order<I: IntoIterator<Item = N>>(&self, items: I) -> Result<Vec<N>, Vec<(N, N)>> {
+        let mut counts = BTreeMap::new();
+        for item in items {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        let restricted = self.restricted_to(counts.keys().cloned());
+        match restricted.topologial_ordering() {
+            Some(order) => Ok(order
+                .into_iter()
+                .flat_map(|item| repeat_n(item.clone(), counts[&item]))
+                .collect()),
+            None => {
+                let cycle = restricted.find_cycle().unwrap();
+                Err((0..cycle.len())
+                    .map(|i| (cycle[i].clone(), cycle[(i + 1) % cycle.len()].clone()))
+                    .collect())
+            }
+        }
+    }
+}
+
+fn bron_kerbosch<'a, N: GraphNode>(
+    neighbors: &BTreeMap<&'a N, BTreeSet<&'a N>>,
+    clique: BTreeSet<&'a N>,
+    mut candidates: BTreeSet<&'a N>,
+    mut excluded: BTreeSet<&'a N>,
+    result: &mut Vec<BTreeSet<&'a N>>,
+) {
+    if candidates.is_empty() && excluded.is_empty() {
+        result.push(clique);
+    } else {
+        let pivot = candidates
+            .union(&excluded)
+            .max_by_key(|p| neighbors[*p].intersection(&candidates).count())
+            .copied()
+            .unwrap();
+        for v in candidates.clone().difference(&neighbors[pivot]) {
+            let v_neighbors = &neighbors[v];
+            let mut next_clique = clique.clone();
+            next_clique.insert(*v);
+            bron_kerbosch(
+                neighbors,
+                next_clique,
+                candidates.intersection(v_neighbors).copied().collect(),
+                excluded.intersection(v_neighbors).copied().collect(),
+                result,
+            );
+            candidates.remove(v);
+            excluded.insert(*v);
+        }
+    }
+}
+
+fn extend_clique<'a, N: GraphNode>(
+    neighbors: &BTreeMap<&'a N, BTreeSet<&'a N>>,
+    clique: BTreeSet<&'a N>,
+    candidates: Vec<&'a N>,
+    k: usize,
+    result: &mut Vec<BTreeSet<&'a N>>,
+) {
+    if clique.len() == k {
+        result.push(clique);
+    } else {
+        for (i, v) in candidates.iter().enumerate() {
+            let mut next_clique = clique.clone();
+            next_clique.insert(*v);
+            let next_candidates = candidates[i + 1..]
+                .iter()
+                .filter(|c| neighbors[v].contains(*c))
+                .copied()
+                .collect();
+            extend_clique(neighbors, next_clique, next_candidates, k, result);
+        }
+    }
+}
+
+struct TarjanState<'a, N> {
+    indices: HashMap<&'a N, usize>,
+    low_links: HashMap<&'a N, usize>,
+    stack: Vec<&'a N>,
+    on_stack: BTreeSet<&'a N>,
+    components: Vec<BTreeSet<N>>,
+}
+
+impl<'a, N> Default for TarjanState<'a, N> {
+    fn default() -> Self {
+        Self {
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: vec![],
+            on_stack: BTreeSet::new(),
+            components: vec![],
+        }
+    }
+}
+
+impl<N: GraphNode + Display> AdjacencySets<N> {
+    // An undirected graph stores each edge both ways; only one is drawn.
+    pub fn dot_graph(&self) -> DotGraph<&N> {
+        let directed = self.is_directed();
+        let mut dot =
+            DotGraph::from_edges(directed, self.pairs().filter(|(a, b)| directed || a <= b));
+        for node in self.keys() {
+            dot = dot.with_node(node);
+        }
+        dot
+    }
+
+    pub fn graphviz(&self, filename: &str) -> anyhow::Result<()> {
+        self.graphviz_labeled(filename, &HashMap::new())
+    }
+
+    pub fn graphviz_labeled(
+        &self,
+        filename: &str,
+        edge_labels: &HashMap<(&N, &N), String>,
+    ) -> anyhow::Result<()> {
+        let directed = self.is_directed();
+        let mut dot = self.dot_graph();
+        for ((src, dest), label) in edge_labels.iter() {
+            // `dot_graph` draws an undirected edge only in its `a <= b` form.
+            let (src, dest) = if directed || src <= dest {
+                (src, dest)
+            } else {
+                (dest, src)
+            };
+            dot = dot.with_edge_attribute(src, dest, "label", label.as_str());
+        }
+        dot.write(filename)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WeightedGraph<N: GraphNode, W: Estimator> {
+    graph: BTreeMap<N, BTreeMap<N, W>>,
+}
+
+impl<N: GraphNode, W: Estimator> Default for WeightedGraph<N, W> {
+    fn default() -> Self {
+        Self {
+            graph: BTreeMap::new(),
+        }
+    }
+}
+
+impl<N: GraphNode, W: Estimator> WeightedGraph<N, W> {
+    pub fn from_unweighted(graph: &AdjacencySets<N>, weight: W) -> Self {
+        let mut result = Self::default();
+        for node in graph.keys() {
+            result.add_node(node.clone());
+        }
+        for (a, b) in graph.pairs() {
+            result.connect(a.clone(), b.clone(), weight);
+        }
+        result
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &N> {
+        self.graph.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.graph.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graph.is_empty()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.graph.values().map(|edges| edges.len()).sum()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N, W)> {
+        self.graph
+            .iter()
+            .flat_map(|(a, edges)| edges.iter().map(move |(b, w)| (a, b, *w)))
+    }
+
+    pub fn neighbors_of(&self, node: &N) -> impl Iterator<Item = (&N, W)> {
+        self.graph.get(node).unwrap().iter().map(|(n, w)| (n, *w))
+    }
+
+    pub fn weight(&self, start: &N, end: &N) -> Option<W> {
+        self.graph
+            .get(start)
+            .and_then(|edges| edges.get(end))
+            .copied()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.graph.entry(node).or_default();
+    }
+
+    // Replaces the weight of any existing edge from `start` to `end`.
+    pub fn connect(&mut self, start: N, end: N, weight: W) {
+        self.add_node(end.clone());
+        self.graph.entry(start).or_default().insert(end, weight);
+    }
+
+    pub fn connect2(&mut self, start: N, end: N, weight: W) {
+        self.connect(start.clone(), end.clone(), weight);
+        self.connect(end, start, weight);
+    }
+
+    // Requires non-negative weights.
+    pub fn dijkstra(&self, start: &N) -> ShortestPaths<N, W> {
+        let mut searcher = PrioritySearchIter::dijkstra(start.clone(), |n| {
+            self.neighbors_of(n).map(|(m, w)| (m.clone(), w)).collect()
+        });
+        searcher.by_ref().last();
+        ShortestPaths {
+            costs: searcher.all_costs(),
+            parents: searcher.parents().clone(),
+        }
+    }
+
+    // Returns None if a negative cycle is reachable from `start`.
+    pub fn bellman_ford(&self, start: &N) -> Option<ShortestPaths<N, W>> {
+        let mut costs = hash_map!(start.clone() => W::zero());
+        let mut parents = hash_map!(start.clone() => None);
+        if self.is_empty() {
+            // No edges means no cycles; only `start` itself is reached.
+            return Some(ShortestPaths { costs, parents });
+        }
+        for _ in 0..self.len() {
+            if !self.relax_all(&mut costs, &mut parents) {
+                return Some(ShortestPaths { costs, parents });
+            }
+        }
+        None
+    }
+
+    fn relax_all(&self, costs: &mut HashMap<N, W>, parents: &mut HashMap<N, Option<N>>) -> bool {
+        let mut changed = false;
+        for (a, b, w) in self.edges() {
+            if let Some(a_cost) = costs.get(a).copied() {
+                let candidate = a_cost + w;
+                if costs.get(b).is_none_or(|b_cost| candidate < *b_cost) {
+                    costs.insert(b.clone(), candidate);
+                    parents.insert(b.clone(), Some(a.clone()));
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    // Returns None if the graph contains a negative cycle.
+    pub fn floyd_warshall(&self) -> Option<AllPairsShortestPaths<N, W>> {
+        let mut costs = HashMap::new();
+        let mut parents = HashMap::new();
+        for node in self.keys() {
+            costs.insert((node.clone(), node.clone()), W::zero());
+            parents.insert((node.clone(), node.clone()), None);
+        }
+        for (a, b, w) in self.edges() {
+            let key = (a.clone(), b.clone());
+            if costs.get(&key).is_none_or(|c| w < *c) {
+                costs.insert(key.clone(), w);
+                parents.insert(key, Some(a.clone()));
+            }
+        }
+        for k in self.keys() {
+            for i in self.keys() {
+                if let Some(ik) = costs.get(&(i.clone(), k.clone())).copied() {
+                    for j in self.keys() {
+                        if let Some(kj) = costs.get(&(k.clone(), j.clone())).copied() {
+                            let key = (i.clone(), j.clone());
+                            if costs.get(&key).is_none_or(|ij| ik + kj < *ij) {
+                                costs.insert(key.clone(), ik + kj);
+                                let parent = parents.get(&(k.clone(), j.clone())).unwrap().clone();
+                                parents.insert(key, parent);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if self
+            .keys()
+            .any(|n| *costs.get(&(n.clone(), n.clone())).unwrap() < W::zero())
+        {
+            None
+        } else {
+            Some(AllPairsShortestPaths { costs, parents })
+        }
+    }
+
+    // Edmonds-Karp, treating each edge weight as a capacity.
+    pub fn max_flow(&self, source: &N, sink: &N) -> MaxFlow<N, W>
+    where
+        W: Sub<Output = W>,
+    {
+        if source == sink {
+            return MaxFlow {
+                value: W::zero(),
+                flows: HashMap::new(),
+                source_side: b_tree_set! {source.clone()},
+                cut_edges: vec![],
+            };
+        }
+        let mut residual = HashMap::new();
+        let mut residual_graph = AdjacencySets::default();
+        for (a, b, capacity) in self.edges() {
+            residual.insert((a.clone(), b.clone()), capacity);
+            residual.entry((b.clone(), a.clone())).or_insert(W::zero());
+            residual_graph.connect2(a, b);
+        }
+        residual_graph.add_node(source.clone());
+        // Antiparallel edges share a residual entry, so the flow on each
+        // original edge is tracked separately.
+        let mut flows = HashMap::new();
+        let mut value = W::zero();
+        loop {
+            let mut searcher = BfsIter::new(source.clone(), |n| {
+                residual_graph
+                    .neighbors_of(n)
+                    .filter(|m| residual[&(n.clone(), (*m).clone())] > W::zero())
+                    .cloned()
+                    .collect()
+            });
+            if searcher.by_ref().find(|n| n == sink).is_none() {
+                let source_side = searcher.all_depths().into_keys().collect::<BTreeSet<_>>();
+                return self.flow_result(value, flows, source_side);
+            }
+            let path = searcher.path_back_from(sink);
+            let edges = path
+                .iter()
+                .skip(1)
+                .zip(path.iter())
+                .map(|(a, b)| (a.clone(), b.clone()))
+                .collect_vec();
+            let bottleneck = edges.iter().map(|e| residual[e]).min().unwrap();
+            for (a, b) in edges {
+                let forward = residual.get_mut(&(a.clone(), b.clone())).unwrap();
+                *forward = *forward - bottleneck;
+                let backward = residual.get_mut(&(b.clone(), a.clone())).unwrap();
+                *backward = *backward + bottleneck;
+                // Cancel flow on b -> a before sending any along a -> b.
+                let opposing = flows
+                    .get(&(b.clone(), a.clone()))
+                    .copied()
+                    .unwrap_or_default();
+                let cancelled = opposing.min(bottleneck);
+                if cancelled > W::zero() {
+                    flows.insert((b.clone(), a.clone()), opposing - cancelled);
+                }
+                if bottleneck > cancelled {
+                    let flow = flows.entry((a, b)).or_insert(W::zero());
+                    *flow = *flow + (bottleneck - cancelled);
+                }
+            }
+            value = value + bottleneck;
+        }
+    }
+
+    fn flow_result(
+        &self,
+        value: W,
+        mut flows: HashMap<(N, N), W>,
+        source_side: BTreeSet<N>,
+    ) -> MaxFlow<N, W> {
+        flows.retain(|_, flow| *flow > W::zero());
+        let mut cut_edges = vec![];
+        for (a, b, _) in self.edges() {
+            if source_side.contains(a) && !source_side.contains(b) {
+                cut_edges.push((a.clone(), b.clone()));
+            }
+        }
+        MaxFlow {
+            value,
+            flows,
+            source_side,
+            cut_edges,
+        }
+    }
+
+    // Stoer-Wagner, for graphs whose edge weights are symmetric.
+    // Returns the weight of the lightest cut and the nodes on one side of it,
+    // or None if there are fewer than two nodes.
+    pub fn global_min_cut(&self) -> Option<(W, BTreeSet<N>)> {
+        let nodes = self.keys().cloned().collect_vec();
+        let index_of = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n, i))
+            .collect::<HashMap<_, _>>();
+        let mut weights = vec![vec![W::zero(); nodes.len()]; nodes.len()];
+        for (a, b, w) in self.edges().filter(|(a, b, _)| a != b) {
+            weights[index_of[a]][index_of[b]] = w;
+        }
+        let mut groups = (0..nodes.len()).map(|i| vec![i]).collect_vec();
+        let mut active = (0..nodes.len()).collect_vec();
+        let mut best: Option<(W, Vec<usize>)> = None;
+        while active.len() > 1 {
+            let mut added = vec![false; nodes.len()];
+            let mut connection = vec![W::zero(); nodes.len()];
+            let mut previous = active[0];
+            for i in 0..active.len() {
+                let selected = active
                     .iter()
-                    .map(|s| s.as_str()),
-            )
-        })
+                    .copied()
+                    .filter(|v| !added[*v])
+                    .max_by_key(|v| connection[*v])
+                    .unwrap();
+                added[selected] = true;
+                if i == active.len() - 1 {
+                    if best.as_ref().is_none_or(|(w, _)| connection[selected] < *w) {
+                        best = Some((connection[selected], groups[selected].clone()));
+                    }
+                    let merged = groups[selected].clone();
+                    groups[previous].extend(merged);
+                    let merged_row = weights[previous]
+                        .iter()
+                        .zip(weights[selected].iter())
+                        .map(|(a, b)| *a + *b)
+                        .collect_vec();
+                    for (v, w) in merged_row.iter().enumerate() {
+                        weights[v][previous] = *w;
+                    }
+                    weights[previous] = merged_row;
+                    active.retain(|v| *v != selected);
+                } else {
+                    for v in active.iter().copied() {
+                        connection[v] = connection[v] + weights[selected][v];
+                    }
+                    previous = selected;
+                }
+            }
+        }
+        best.map(|(w, side)| (w, side.iter().map(|i| nodes[*i].clone()).collect()))
     }
+}
 
-    pub fn graphviz(&self, filename: &str) -> anyhow::Result<()> {
-        let dummy = HashMap::new();
-        if self.is_directed() {
-            graphviz_directed(self.pairs(), filename, &dummy)
-        } else {
-            graphviz_undirected(self.pairs(), filename, &dummy)
-        }
+#[derive(Debug, Clone)]
+pub struct MaxFlow<N: GraphNode, W: Estimator> {
+    value: W,
+    flows: HashMap<(N, N), W>,
+    source_side: BTreeSet<N>,
+    cut_edges: Vec<(N, N)>,
+}
+
+impl<N: GraphNode, W: Estimator> MaxFlow<N, W> {
+    pub fn value(&self) -> W {
+        self.value
     }
 
-    pub fn graphviz_labeled(&self, filename: &str, edge_labels: &HashMap<(&str,&str), String>) -> anyhow::Result<()> {
-        if self.is_directed() {
-            graphviz_directed(self.pairs(), filename, edge_labels)
-        } else {
-            graphviz_undirected(self.pairs(), filename, edge_labels)
-        }
+    pub fn flow_on(&self, start: &N, end: &N) -> W {
+        self.flows
+            .get(&(start.clone(), end.clone()))
+            .copied()
+            .unwrap_or_default()
     }
 
-    pub fn neighbors_of(&self, node: &str) -> impl Iterator<Item = &str> {
-        self.graph.get(node).unwrap().iter().map(|s| s.as_str())
+    // Nodes still reachable from the source in the residual graph.
+    pub fn source_side(&self) -> &BTreeSet<N> {
+        &self.source_side
     }
 
-    pub fn are_connected(&self, start: &str, end: &str) -> bool {
-        self.graph.get(start).map_or(false, |set| set.contains(end))
+    // A minimum cut: the edges from the source side to the sink side.
+    pub fn cut_edges(&self) -> &Vec<(N, N)> {
+        &self.cut_edges
     }
+}
 
-    pub fn connect2(&mut self, start: &str, end: &str) {
-        self.connect(start, end);
-        self.connect(end, start);
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N: GraphNode, W: Estimator> {
+    costs: HashMap<N, W>,
+    parents: HashMap<N, Option<N>>,
+}
+
+impl<N: GraphNode, W: Estimator> ShortestPaths<N, W> {
+    pub fn path_back_from(&self, node: &N) -> VecDeque<N> {
+        path_back_from(node, &self.parents)
     }
 
-    pub fn connect(&mut self, start: &str, end: &str) {
-        match self.graph.get_mut(start) {
-            None => {
-                self.graph
-                    .insert(start.to_string(), b_tree_set! {end.to_string()});
-            }
-            Some(connections) => {
-                connections.insert(end.to_string());
-            }
-        }
-        if !self.graph.contains_key(end) {
-            self.graph.insert(end.to_string(), BTreeSet::new());
-        }
+    pub fn cost_for(&self, node: &N) -> W {
+        self.costs.get(node).copied().unwrap()
     }
 
-    pub fn in_degrees(&self) -> HashMap<String, usize> {
-        self.degrees(
-            self.pairs()
-                .map(|(_, dest)| dest)
-                .collect::<HashHistogram<_>>(),
-        )
+    pub fn is_reachable(&self, node: &N) -> bool {
+        self.costs.contains_key(node)
     }
 
-    pub fn out_degrees(&self) -> HashMap<String, usize> {
-        self.degrees(
-            self.pairs()
-                .map(|(src, _)| src)
-                .collect::<HashHistogram<_>>(),
-        )
+    pub fn all_costs(&self) -> HashMap<N, W> {
+        self.costs.clone()
     }
+}
 
-    fn degrees(&self, above_zero: HashHistogram<&str>) -> HashMap<String, usize> {
-        let mut result = above_zero
-            .iter()
-            .map(|(s, c)| (s.to_string(), *c))
-            .collect::<HashMap<_, _>>();
-        for v in self.graph.keys() {
-            if !result.contains_key(v) {
-                result.insert(v.clone(), 0);
-            }
+#[derive(Debug, Clone)]
+pub struct AllPairsShortestPaths<N: GraphNode, W: Estimator> {
+    costs: HashMap<(N, N), W>,
+    parents: HashMap<(N, N), Option<N>>,
+}
+
+impl<N: GraphNode, W: Estimator> AllPairsShortestPaths<N, W> {
+    // Like `ShortestPaths::path_back_from`, runs from `node` back to `start`.
+    // Returns None if `node` is unreachable from `start`.
+    pub fn path_back_from(&self, start: &N, node: &N) -> Option<VecDeque<N>> {
+        let mut result = VecDeque::new();
+        let mut current = node.clone();
+        result.push_back(current.clone());
+        while let Some(parent) = self.parents.get(&(start.clone(), current))? {
+            result.push_back(parent.clone());
+            current = parent.clone();
         }
-        result
+        Some(result)
     }
 
-    // Kahn's Algorithm
-    // Returns None if the graph contains a cycle.
-    pub fn topologial_ordering(&self) -> Option<Vec<String>> {
-        let mut in_degrees = self.in_degrees();
-        let source_nodes = in_degrees
-            .iter()
-            .filter(|(_, c)| **c == 0)
-            .map(|(n, _)| n.clone())
-            .collect_vec();
-        let visited = BfsIter::multi_start(source_nodes.iter().cloned(), |n| {
-            let mut next = vec![];
-            for neighbor in self.neighbors_of(n.as_str()) {
-                let count = in_degrees.get_mut(neighbor).unwrap();
-                *count -= 1;
-                if *count == 0 {
-                    next.push(neighbor.to_string());
-                }
-            }
-            next
-        })
-        .collect_vec();
-        if visited.len() == self.len() {
-            Some(visited)
-        } else {
-            None
-        }
+    pub fn cost_for(&self, start: &N, node: &N) -> W {
+        self.costs
+            .get(&(start.clone(), node.clone()))
+            .copied()
+            .unwrap()
+    }
+
+    pub fn is_reachable(&self, start: &N, node: &N) -> bool {
+        self.costs.contains_key(&(start.clone(), node.clone()))
     }
 }
 
@@ -169,7 +1381,7 @@ pub fn graphviz_undirected<N: GraphVizItem, I: Iterator<Item = (N, N)>>(
     output_filename: &str,
     edge_labels: &HashMap<(N,N), String>,
 ) -> anyhow::Result<()> {
-    graphviz(items, output_filename, "graph", "--", edge_labels)
+    graphviz(items, output_filename, false, edge_labels)
 }
 
 pub fn graphviz_directed<N: GraphVizItem, I: Iterator<Item = (N, N)>>(
@@ -177,41 +1389,215 @@ pub fn graphviz_directed<N: GraphVizItem, I: Iterator<Item = (N, N)>>(
     output_filename: &str,
     edge_labels: &HashMap<(N,N), String>,
 ) -> anyhow::Result<()> {
-    graphviz(items, output_filename, "digraph", "->", edge_labels)
+    graphviz(items, output_filename, true, edge_labels)
 }
 
 fn graphviz<N: GraphVizItem, I: Iterator<Item = (N, N)>>(
     items: I,
     output_filename: &str,
-    header: &str,
-    edge: &str,
+    directed: bool,
     edge_labels: &HashMap<(N,N), String>,
 ) -> anyhow::Result<()> {
-    let mut file_out = std::fs::File::create(output_filename)?;
-    writeln!(file_out, "{header} G {{")?;
+    let mut dot = DotGraph::new(directed);
     for (src, dest) in items {
-        let mut label = String::new();
-        if let Some(edge_label) = edge_labels.get(&(src.clone(), dest.clone())) {
-            label = format!("[label = \"{edge_label}\"]")
+        let label = edge_labels.get(&(src.clone(), dest.clone())).cloned();
+        dot = dot.with_edge(src.clone(), dest.clone());
+        if let Some(label) = label {
+            dot = dot.with_edge_attribute(src, dest, "label", label.as_str());
+        }
+    }
+    dot.write(output_filename)
+}
+
+// Builds a DOT description one piece at a time. Node names and attribute
+// values are quoted and escaped on output, so any `Display` text is safe.
+#[derive(Clone, Debug)]
+pub struct DotGraph<N: GraphVizItem> {
+    directed: bool,
+    graph_attributes: Vec<(String, String)>,
+    nodes: Vec<N>,
+    node_set: HashSet<N>,
+    node_attributes: HashMap<N, BTreeMap<String, String>>,
+    edges: Vec<(N, N)>,
+    edge_attributes: HashMap<(N, N), BTreeMap<String, String>>,
+    clusters: Vec<(String, Vec<N>)>,
+}
+
+impl<N: GraphVizItem> DotGraph<N> {
+    pub fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            graph_attributes: vec![],
+            nodes: vec![],
+            node_set: HashSet::new(),
+            node_attributes: HashMap::new(),
+            edges: vec![],
+            edge_attributes: HashMap::new(),
+            clusters: vec![],
+        }
+    }
+
+    pub fn from_edges<I: Iterator<Item = (N, N)>>(directed: bool, edges: I) -> Self {
+        edges.fold(Self::new(directed), |dot, (a, b)| dot.with_edge(a, b))
+    }
+
+    pub fn with_graph_attribute(mut self, key: &str, value: &str) -> Self {
+        self.graph_attributes
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_node(mut self, node: N) -> Self {
+        if self.node_set.insert(node.clone()) {
+            self.nodes.push(node);
+        }
+        self
+    }
+
+    pub fn with_edge(mut self, start: N, end: N) -> Self {
+        self = self.with_node(start.clone()).with_node(end.clone());
+        self.edges.push((start, end));
+        self
+    }
+
+    pub fn with_node_attribute(mut self, node: N, key: &str, value: &str) -> Self {
+        self = self.with_node(node.clone());
+        self.node_attributes
+            .entry(node)
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_edge_attribute(mut self, start: N, end: N, key: &str, value: &str) -> Self {
+        self.edge_attributes
+            .entry((start, end))
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_node_label(self, node: N, label: &str) -> Self {
+        self.with_node_attribute(node, "label", label)
+    }
+
+    pub fn with_node_color(self, node: N, color: &str) -> Self {
+        self.with_node_attribute(node, "color", color)
+    }
+
+    pub fn with_node_shape(self, node: N, shape: &str) -> Self {
+        self.with_node_attribute(node, "shape", shape)
+    }
+
+    pub fn with_cluster<I: IntoIterator<Item = N>>(mut self, name: &str, nodes: I) -> Self {
+        let nodes = nodes.into_iter().collect_vec();
+        for node in nodes.iter() {
+            self = self.with_node(node.clone());
+        }
+        self.clusters.push((name.to_string(), nodes));
+        self
+    }
+
+    pub fn highlight_nodes<I: IntoIterator<Item = N>>(mut self, nodes: I, color: &str) -> Self {
+        for node in nodes {
+            self = self
+                .with_node_color(node.clone(), color)
+                .with_node_attribute(node, "style", "filled")
+        }
+        self
+    }
+
+    // Colors the nodes of `path` and every edge between consecutive nodes.
+    pub fn highlight_path(mut self, path: &[N], color: &str) -> Self {
+        self = self.highlight_nodes(path.iter().cloned(), color);
+        for (a, b) in path.iter().tuple_windows() {
+            for (start, end) in [(a, b), (b, a)] {
+                self = self
+                    .with_edge_attribute(start.clone(), end.clone(), "color", color)
+                    .with_edge_attribute(start.clone(), end.clone(), "penwidth", "3");
+            }
+        }
+        self
+    }
+
+    pub fn write(&self, output_filename: &str) -> anyhow::Result<()> {
+        let mut file_out = std::fs::File::create(output_filename)?;
+        write!(file_out, "{self}")?;
+        Ok(())
+    }
+}
+
+impl<N: GraphVizItem> Display for DotGraph<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (header, edge) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(f, "{header} G {{")?;
+        for (key, value) in self.graph_attributes.iter() {
+            writeln!(f, "  {key} = {}", dot_quoted(value))?;
+        }
+        for (i, (name, nodes)) in self.clusters.iter().enumerate() {
+            writeln!(f, "  subgraph cluster_{i} {{")?;
+            writeln!(f, "    label = {}", dot_quoted(name))?;
+            for node in nodes.iter() {
+                writeln!(f, "    {}", dot_quoted(&node.to_string()))?;
+            }
+            writeln!(f, "  }}")?;
+        }
+        for node in self.nodes.iter() {
+            writeln!(
+                f,
+                "  {}{}",
+                dot_quoted(&node.to_string()),
+                dot_attributes(self.node_attributes.get(node))
+            )?;
         }
-        writeln!(file_out, "  {src} {edge} {dest} {label}")?;
+        for (src, dest) in self.edges.iter() {
+            let attributes = self.edge_attributes.get(&(src.clone(), dest.clone()));
+            writeln!(
+                f,
+                "  {} {edge} {}{}",
+                dot_quoted(&src.to_string()),
+                dot_quoted(&dest.to_string()),
+                dot_attributes(attributes)
+            )?;
+        }
+        writeln!(f, "}}")
     }
-    writeln!(file_out, "}}")?;
-    Ok(())
+}
+
+fn dot_quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn dot_attributes(attributes: Option<&BTreeMap<String, String>>) -> String {
+    attributes.map_or(String::new(), |attributes| {
+        let pairs = attributes
+            .iter()
+            .map(|(key, value)| format!("{key} = {}", dot_quoted(value)))
+            .join(", ");
+        format!(" [{pairs}]")
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
+    use std::{collections::BTreeSet, fmt::Display, str::FromStr};
 
-    use crate::{graph::AdjacencySets, search_iter::BfsIter};
+    use itertools::Itertools;
 
-    // TODO: Need more tests for the following:
-    // * Directed graph example
+    use crate::{
+        all_lines,
+        graph::{AdjacencySets, DotGraph, GraphNode, PrecedenceRules, WeightedGraph},
+        multidim::Position,
+        search_iter::BfsIter,
+    };
 
     #[test]
     fn graph_test() {
-        let mut graph = AdjacencySets::default();
+        let mut graph: AdjacencySets = AdjacencySets::default();
         for (a, b) in [
             ("start", "A"),
             ("start", "b"),
@@ -229,18 +1615,20 @@ mod tests {
 
         let keys = graph.keys().collect::<Vec<_>>();
         assert_eq!(keys, vec!["A", "b", "c", "d", "end", "start"]);
-        let mut searcher = BfsIter::new("start", |s| graph.neighbors_of(s).collect());
+        let mut searcher = BfsIter::new("start".to_string(), |s| {
+            graph.neighbors_of(s).cloned().collect()
+        });
         let found = searcher.by_ref().collect_vec();
         assert_eq!(found, vec!["start", "A", "b", "c", "end", "d"]);
 
-        let path = searcher.path_back_from(&"end");
+        let path = searcher.path_back_from(&"end".to_string());
         let path_str = format!("{:?}", path);
         assert_eq!(path_str, r#"["end", "A", "start"]"#);
     }
 
     #[test]
     fn test_pair_iter() {
-        let mut graph = AdjacencySets::default();
+        let mut graph: AdjacencySets = AdjacencySets::default();
         for (a, b) in [
             ("start", "A"),
             ("start", "b"),
@@ -259,4 +1647,520 @@ mod tests {
             r#"[("A", "b"), ("A", "c"), ("A", "end"), ("A", "start"), ("b", "A"), ("b", "d"), ("b", "end"), ("b", "start"), ("c", "A"), ("d", "b"), ("end", "A"), ("end", "b"), ("start", "A"), ("start", "b")]"#
         );
     }
+
+    #[test]
+    fn test_generic_directed() {
+        let mut graph = AdjacencySets::default();
+        for (a, b) in [
+            ((0, 0), (1, 0)),
+            ((1, 0), (1, 1)),
+            ((0, 0), (0, 1)),
+            ((0, 1), (1, 1)),
+        ] {
+            graph.connect(&Position::from(a), &Position::from(b));
+        }
+        assert!(graph.is_directed());
+        assert_eq!(graph.num_edges(), 4);
+        assert_eq!(graph.in_degrees().get(&Position::from((1, 1))), Some(&2));
+        assert_eq!(graph.out_degrees().get(&Position::from((1, 1))), Some(&0));
+
+        let order = graph.topologial_ordering().unwrap();
+        assert_eq!(order.first(), Some(&Position::from((0, 0))));
+        assert_eq!(order.last(), Some(&Position::from((1, 1))));
+
+        graph.connect(&Position::from((1, 1)), &Position::from((0, 0)));
+        assert_eq!(graph.topologial_ordering(), None);
+    }
+
+    fn weighted_example() -> WeightedGraph<char, isize> {
+        let mut graph = WeightedGraph::default();
+        for (a, b, w) in [
+            ('a', 'b', 4),
+            ('a', 'c', 1),
+            ('c', 'b', 2),
+            ('b', 'd', 1),
+            ('c', 'd', 5),
+            ('d', 'e', 3),
+        ] {
+            graph.connect(a, b, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_weighted_single_source() {
+        let graph = weighted_example();
+        assert_eq!(graph.num_edges(), 6);
+        assert_eq!(graph.weight(&'c', &'b'), Some(2));
+        for paths in [graph.dijkstra(&'a'), graph.bellman_ford(&'a').unwrap()] {
+            assert_eq!(paths.cost_for(&'e'), 7);
+            assert_eq!(
+                paths.path_back_from(&'e').iter().collect::<String>(),
+                "edbca"
+            );
+        }
+        assert!(!graph.dijkstra(&'e').is_reachable(&'a'));
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        let mut graph = weighted_example();
+        graph.connect('e', 'c', -4);
+        let paths = graph.bellman_ford(&'a').unwrap();
+        assert_eq!(paths.cost_for(&'e'), 7);
+        let all = graph.floyd_warshall().unwrap();
+        assert_eq!(all.cost_for(&'e', &'d'), -1);
+        assert_eq!(
+            all.path_back_from(&'e', &'d')
+                .unwrap()
+                .iter()
+                .collect::<String>(),
+            "dbce"
+        );
+        assert_eq!(all.cost_for(&'a', &'e'), 7);
+        assert!(!all.is_reachable(&'b', &'a'));
+        assert_eq!(all.path_back_from(&'b', &'a'), None);
+
+        let empty = WeightedGraph::<char, i64>::default();
+        let paths = empty.bellman_ford(&'a').unwrap();
+        assert_eq!(paths.cost_for(&'a'), 0);
+        assert!(!paths.is_reachable(&'b'));
+
+        graph.connect('e', 'c', -7);
+        assert!(graph.bellman_ford(&'a').is_none());
+        assert!(graph.floyd_warshall().is_none());
+    }
+
+    fn day23_example() -> AdjacencySets {
+        let mut graph = AdjacencySets::default();
+        for line in all_lines("ex/day23.txt").unwrap() {
+            let (a, b) = line.split('-').collect_tuple().unwrap();
+            graph.connect2(a, b);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_cliques() {
+        let graph = day23_example();
+        let triangles = graph.triangles();
+        assert_eq!(triangles.len(), 12);
+        assert_eq!(
+            triangles
+                .iter()
+                .filter(|c| c.iter().any(|n| n.starts_with('t')))
+                .count(),
+            7
+        );
+        assert_eq!(graph.maximum_clique().iter().join(","), "co,de,ka,ta");
+        assert_eq!(graph.count_k_cliques(4), 1);
+        assert_eq!(graph.count_k_cliques(5), 0);
+        assert_eq!(graph.count_k_cliques(2), graph.num_symmetric_edges());
+        assert_eq!(graph.count_k_cliques(1), graph.len());
+
+        let maximal = graph.maximal_cliques();
+        assert!(maximal.iter().all(|c| graph
+            .keys()
+            .all(|n| c.contains(n) || c.iter().any(|m| !graph.are_connected(*m, n)))));
+        assert_eq!(maximal.iter().filter(|c| c.len() == 4).count(), 1);
+    }
+
+    #[test]
+    fn test_cliques_with_self_loop() {
+        let mut graph = AdjacencySets::default();
+        graph.connect2("a", "b");
+        graph.connect2("b", "c");
+        graph.connect2("a", "a");
+        let maximal = graph
+            .maximal_cliques()
+            .iter()
+            .map(|c| c.iter().join(","))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(maximal, ["a,b", "b,c"].map(String::from).into());
+        assert_eq!(graph.maximum_clique().len(), 2);
+    }
+
+    #[test]
+    fn test_cliques_one_direction() {
+        // Each edge is stored once, pointing either way.
+        let mut graph = AdjacencySets::default();
+        for (a, b) in [("a", "b"), ("c", "b"), ("a", "c"), ("d", "c")] {
+            graph.connect(a, b);
+        }
+        assert_eq!(graph.maximum_clique().iter().join(","), "a,b,c");
+        assert_eq!(graph.triangles().len(), 1);
+        assert_eq!(graph.maximal_cliques().len(), 2);
+        assert_eq!(graph.count_k_cliques(2), 4);
+    }
+
+    fn cyclic_example() -> AdjacencySets<char> {
+        let mut graph = AdjacencySets::default();
+        for (a, b) in [
+            ('a', 'b'),
+            ('b', 'c'),
+            ('c', 'a'),
+            ('c', 'd'),
+            ('d', 'e'),
+            ('e', 'd'),
+            ('e', 'f'),
+            ('b', 'a'),
+        ] {
+            graph.connect(&a, &b);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_strongly_connected() {
+        let graph = cyclic_example();
+        let components = graph.strongly_connected_components();
+        let as_strings = components
+            .iter()
+            .map(|c| c.iter().collect::<String>())
+            .collect_vec();
+        assert_eq!(as_strings, vec!["f", "de", "abc"]);
+
+        let (components, dag) = graph.condensation();
+        assert_eq!(components.len(), 3);
+        assert!(dag.is_dag());
+        assert_eq!(dag.topologial_ordering(), Some(vec![2, 1, 0]));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut graph = cyclic_example();
+        assert!(!graph.is_dag());
+        let cycle = graph.find_cycle().unwrap();
+        for i in 0..cycle.len() {
+            assert!(graph.are_connected(&cycle[i], &cycle[(i + 1) % cycle.len()]));
+        }
+
+        let cycles = graph
+            .elementary_cycles()
+            .iter()
+            .map(|c| c.iter().collect::<String>())
+            .sorted()
+            .collect_vec();
+        assert_eq!(cycles, vec!["ab", "abc", "de"]);
+
+        let mut dag = AdjacencySets::default();
+        for (a, b) in graph.pairs() {
+            if a < b {
+                dag.connect(a, b);
+            }
+        }
+        assert!(dag.is_dag());
+        assert!(dag.elementary_cycles().is_empty());
+        graph.connect(&'f', &'f');
+        assert!(graph.elementary_cycles().contains(&vec!['f']));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let rules = [
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (75, 29),
+            (61, 13),
+            (29, 13),
+            (97, 47),
+        ]
+        .into_iter()
+        .collect::<PrecedenceRules<i64>>();
+        assert!(rules.is_satisfied_by(&[97, 61, 13]));
+        assert!(!rules.is_satisfied_by(&[61, 97, 13]));
+        assert_eq!(rules.order([13, 61, 97]), Ok(vec![97, 61, 13]));
+        assert_eq!(rules.order([5, 75]).map(|o| o.len()), Ok(2));
+        assert_eq!(rules.order([13, 97, 13, 61]), Ok(vec![97, 61, 13, 13]));
+
+        let restricted = rules.restricted_to([97, 13, 29]);
+        assert_eq!(restricted.num_edges(), 2);
+
+        let mut rules = rules;
+        rules.add_rule(13, 97);
+        assert_eq!(rules.order([13, 61, 97, 75]), Err(vec![(13, 97), (97, 13)]));
+        let order = rules.order([61, 97, 75]).unwrap();
+        assert!(rules.is_satisfied_by(&order));
+    }
+
+    #[test]
+    fn test_connectivity() {
+        // Two triangles joined through the bridge c-d, plus a separate pair.
+        let mut graph = AdjacencySets::default();
+        for (a, b) in [
+            ('a', 'b'),
+            ('b', 'c'),
+            ('c', 'a'),
+            ('c', 'd'),
+            ('d', 'e'),
+            ('e', 'f'),
+            ('f', 'd'),
+            ('x', 'y'),
+        ] {
+            graph.connect2(&a, &b);
+        }
+        assert!(!graph.is_directed());
+        let components = graph
+            .connected_components()
+            .iter()
+            .map(|c| c.iter().collect::<String>())
+            .collect_vec();
+        assert_eq!(components, vec!["abcdef", "xy"]);
+        assert!(!graph.is_connected());
+
+        assert_eq!(
+            graph.bridges().into_iter().collect_vec(),
+            vec![('c', 'd'), ('x', 'y')]
+        );
+        assert_eq!(
+            graph.articulation_points().into_iter().collect::<String>(),
+            "cd"
+        );
+        let blocks = graph
+            .biconnected_components()
+            .iter()
+            .map(|c| c.iter().collect::<String>())
+            .sorted()
+            .collect_vec();
+        assert_eq!(blocks, vec!["abc", "cd", "def", "xy"]);
+
+        let mut directed = AdjacencySets::default();
+        directed.connect(&1, &2);
+        directed.connect(&3, &2);
+        assert!(directed.is_directed());
+        assert_eq!(directed.connected_components().len(), 1);
+        directed.connect(&2, &1);
+        directed.connect(&2, &3);
+        assert!(!directed.is_directed());
+        directed.connect(&4, &4);
+        assert!(directed.is_directed());
+    }
+
+    #[test]
+    fn test_max_flow() {
+        let mut graph = WeightedGraph::default();
+        for (a, b, c) in [
+            ('s', 'a', 16),
+            ('s', 'c', 13),
+            ('a', 'b', 12),
+            ('c', 'a', 4),
+            ('b', 'c', 9),
+            ('c', 'd', 14),
+            ('d', 'b', 7),
+            ('b', 't', 20),
+            ('d', 't', 4),
+        ] {
+            graph.connect(a, b, c);
+        }
+        let flow = graph.max_flow(&'s', &'t');
+        assert_eq!(flow.value(), 23);
+        let cut = flow
+            .cut_edges()
+            .iter()
+            .map(|(a, b)| graph.weight(a, b).unwrap());
+        assert_eq!(cut.sum::<i32>(), 23);
+        assert!(flow.source_side().contains(&'s'));
+        assert!(!flow.source_side().contains(&'t'));
+        assert_eq!(flow.flow_on(&'b', &'t') + flow.flow_on(&'d', &'t'), 23);
+        assert_eq!(flow.flow_on(&'t', &'s'), 0);
+
+        assert_eq!(graph.max_flow(&'t', &'s').value(), 0);
+
+        let same = graph.max_flow(&'a', &'a');
+        assert_eq!(same.value(), 0);
+        assert!(same.cut_edges().is_empty());
+        assert_eq!(same.flow_on(&'a', &'b'), 0);
+    }
+
+    #[test]
+    fn test_max_flow_antiparallel() {
+        let mut graph = WeightedGraph::<char, usize>::default();
+        for (a, b, c) in [('s', 'b', 5), ('b', 'a', 5), ('a', 't', 5), ('a', 'b', 1)] {
+            graph.connect(a, b, c);
+        }
+        let flow = graph.max_flow(&'s', &'t');
+        assert_eq!(flow.value(), 5);
+        assert_eq!(flow.flow_on(&'b', &'a'), 5);
+        assert_eq!(flow.flow_on(&'a', &'b'), 0);
+        assert_eq!(flow.flow_on(&'a', &'t'), 5);
+
+        // Flow first sent along a -> b is cancelled by the later b -> a path.
+        let mut graph = WeightedGraph::<char, i64>::default();
+        for (a, b, c) in [
+            ('s', 'a', 1),
+            ('a', 'b', 1),
+            ('b', 't', 1),
+            ('s', 'b', 1),
+            ('b', 'a', 1),
+            ('a', 't', 1),
+        ] {
+            graph.connect(a, b, c);
+        }
+        let flow = graph.max_flow(&'s', &'t');
+        assert_eq!(flow.value(), 2);
+        for node in ['a', 'b'] {
+            let inflow = ['s', 'a', 'b']
+                .iter()
+                .map(|n| flow.flow_on(n, &node))
+                .sum::<i64>();
+            let outflow = ['a', 'b', 't']
+                .iter()
+                .map(|n| flow.flow_on(&node, n))
+                .sum::<i64>();
+            assert_eq!(inflow, outflow);
+        }
+        assert!(flow.flow_on(&'a', &'b') == 0 || flow.flow_on(&'b', &'a') == 0);
+    }
+
+    #[test]
+    fn test_global_min_cut() {
+        let mut graph: AdjacencySets = AdjacencySets::default();
+        for group in ["abcde", "vwxyz"] {
+            for (a, b) in group.chars().tuple_combinations() {
+                graph.connect2(a.to_string().as_str(), b.to_string().as_str());
+            }
+        }
+        for (a, b) in [("a", "w"), ("b", "x"), ("c", "y")] {
+            graph.connect2(a, b);
+        }
+        let (size, side) = graph.global_min_cut().unwrap();
+        assert_eq!(size, 3);
+        let side = side.iter().join("");
+        assert!(side == "abcde" || side == "vwxyz");
+
+        let single: AdjacencySets<i32> = AdjacencySets::default();
+        assert_eq!(single.global_min_cut(), None);
+    }
+
+    #[test]
+    fn test_dot_graph() {
+        let dot = DotGraph::from_edges(true, [("a b", "c"), ("c", "say \"hi\"")].into_iter())
+            .with_graph_attribute("rankdir", "LR")
+            .with_node_shape("c", "box")
+            .with_cluster("inputs", ["a b"])
+            .highlight_path(&["a b", "c"], "red");
+        assert_eq!(
+            format!("{dot}"),
+            r#"digraph G {
+  rankdir = "LR"
+  subgraph cluster_0 {
+    label = "inputs"
+    "a b"
+  }
+  "a b" [color = "red", style = "filled"]
+  "c" [color = "red", shape = "box", style = "filled"]
+  "say \"hi\""
+  "a b" -> "c" [color = "red", penwidth = "3"]
+  "c" -> "say \"hi\""
+}
+"#
+        );
+
+        let mut graph: AdjacencySets = AdjacencySets::default();
+        graph.connect2("x", "y");
+        graph.add_node("z".to_string());
+        let dot = format!("{}", graph.dot_graph());
+        assert!(dot.starts_with("graph G {"));
+        assert!(dot.contains("  \"z\"\n"));
+        assert_eq!(dot.matches(" -- ").count(), 1);
+        assert_eq!(dot.matches("  \"x\" -- \"y\"\n").count(), 1);
+        assert_eq!(dot.matches("  \"x\"\n").count(), 1);
+
+        let filename = std::env::temp_dir().join("advent2024_labeled.dot");
+        let filename = filename.to_str().unwrap();
+        let (x, y) = ("x".to_string(), "y".to_string());
+        graph
+            .graphviz_labeled(filename, &[((&y, &x), "xy".to_string())].into())
+            .unwrap();
+        let dot = std::fs::read_to_string(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+        assert!(dot.contains("  \"x\" -- \"y\" [label = \"xy\"]\n"));
+    }
+
+    #[test]
+    fn test_parse_edge_list() {
+        let text = std::fs::read_to_string("ex/day23.txt").unwrap();
+        let graph = AdjacencySets::<String>::from_edge_list(text.as_str(), "-", true).unwrap();
+        assert_eq!(graph, day23_example());
+        let reparsed = AdjacencySets::from_edge_list(graph.to_edge_list("-").as_str(), "-", false);
+        assert_eq!(reparsed.unwrap(), graph);
+
+        let directed = AdjacencySets::<i32>::from_edge_list("1->2\n2 -> 3\n\n", "->", false);
+        let directed = directed.unwrap();
+        assert!(directed.is_directed());
+        assert_eq!(directed.topologial_ordering(), Some(vec![1, 2, 3]));
+        assert_dot_round_trip("directed_edge_list", &directed);
+        assert_dot_round_trip("edge_list", &graph);
+
+        assert!(AdjacencySets::<i32>::from_edge_list("1-x", "-", true).is_err());
+        assert!(AdjacencySets::<i32>::from_edge_list("1 2", "-", true).is_err());
+    }
+
+    #[test]
+    fn test_parse_adjacency_lines() {
+        let graph = AdjacencySets::<String>::from_adjacency_lines("a: b c\nb: c\nd:\n").unwrap();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.to_adjacency_lines(), "a: b c\nb: c\nc: \nd: \n");
+        assert_eq!(
+            AdjacencySets::from_adjacency_lines(graph.to_adjacency_lines().as_str()).unwrap(),
+            graph
+        );
+        assert_dot_round_trip("adjacency_lines", &graph);
+    }
+
+    #[test]
+    fn test_parse_dot() {
+        let graph = AdjacencySets::<String>::from_dot(
+            r#"digraph G {
+  rankdir = LR; node [shape = box]
+  subgraph cluster_0 { label = "x"; a }
+  a -> "b c" -> d [label = "a -> ] b"]
+  e
+}"#,
+        )
+        .unwrap();
+        assert_eq!(graph.keys().join(","), "a,b c,d,e");
+        assert!(graph.are_connected("b c", "d"));
+        assert_eq!(graph.num_edges(), 2);
+        assert!(AdjacencySets::<String>::from_dot("strict G {}").is_err());
+
+        let graph = AdjacencySets::<String>::from_dot(
+            r#"# preprocessor line
+graph {
+  // a -- x
+  a --
+    b /* b -- y */ c
+  b -- {c d}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(graph.keys().join(","), "a,b,c,d");
+        assert_eq!(graph.num_symmetric_edges(), 3);
+        assert!(graph.are_connected("a", "b"));
+        assert!(graph.are_connected("d", "b"));
+        for unsupported in [
+            "digraph { a:n -> b }",
+            "digraph { a -> <b> }",
+            "digraph { a -- b }",
+            "digraph { a -> }",
+            "digraph { a /* b }",
+            "digraph { a -> b",
+        ] {
+            assert!(AdjacencySets::<String>::from_dot(unsupported).is_err());
+        }
+    }
+
+    fn assert_dot_round_trip<N: GraphNode + Display + FromStr>(name: &str, graph: &AdjacencySets<N>)
+    where
+        <N as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+    {
+        let filename = std::env::temp_dir().join(format!("advent2024_{name}.dot"));
+        let filename = filename.to_str().unwrap();
+        graph.graphviz(filename).unwrap();
+        let text = std::fs::read_to_string(filename).unwrap();
+        std::fs::remove_file(filename).unwrap();
+        assert_eq!(AdjacencySets::from_dot(text.as_str()).unwrap(), *graph);
+    }
 }
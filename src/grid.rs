@@ -1,12 +1,18 @@
 use bare_metal_modulo::*;
+use enum_iterator::all;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     fmt::{Debug, Display},
     str::FromStr,
 };
-
-use crate::multidim::{
-    map_width_height, to_map, DirType, Position, RingIterator, RowMajorPositionIterator,
+use trait_set::trait_set;
+
+use crate::{
+    graph::WeightedGraph,
+    multidim::{
+        map_width_height, to_map, DirType, Position, RingIterator, RowMajorPositionIterator,
+    },
+    search_iter::BfsIter,
 };
 
 pub type GridDigitWorld = GridWorld<ModNumC<u8, 10>>;
@@ -173,6 +179,259 @@ impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
     pub fn len(&self) -> usize {
         self.map.len()
     }
+
+    pub fn neighbors<D: DirType>(&self, p: Position) -> Vec<Position> {
+        all::<D>()
+            .map(|d| d.neighbor(p))
+            .filter(|n| self.in_bounds(*n))
+            .collect()
+    }
+
+    pub fn passable_neighbors<D: DirType, P: Fn(V) -> bool>(
+        &self,
+        p: Position,
+        passable: P,
+    ) -> Vec<Position> {
+        self.neighbors::<D>(p)
+            .into_iter()
+            .filter(|n| passable(self.value(*n).unwrap()))
+            .collect()
+    }
+
+    // Flood fill from every seed, stepping only onto cells whose values satisfy
+    // `passable`. Seeds are always included.
+    pub fn reachable_from<D: DirType, I: Iterator<Item = Position>, P: Fn(V) -> bool>(
+        &self,
+        seeds: I,
+        passable: P,
+    ) -> BTreeSet<Position> {
+        BfsIter::multi_start(seeds, |p| self.passable_neighbors::<D, _>(*p, &passable)).collect()
+    }
+
+    // Each cell holds its step count from the nearest seed, or None if unreachable.
+    pub fn distances_from<D: DirType, I: Iterator<Item = Position>, P: Fn(V) -> bool>(
+        &self,
+        seeds: I,
+        passable: P,
+    ) -> GridWorld<Option<usize>> {
+        let mut searcher =
+            BfsIter::multi_start(seeds, |p| self.passable_neighbors::<D, _>(*p, &passable));
+        searcher.by_ref().last();
+        let depths = searcher.all_depths();
+        GridWorld {
+            map: self
+                .map
+                .keys()
+                .map(|p| (*p, depths.get(p).copied()))
+                .collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // Returns the positions from `start` to `goal` inclusive.
+    pub fn shortest_path<D: DirType, P: Fn(V) -> bool>(
+        &self,
+        start: Position,
+        goal: Position,
+        passable: P,
+    ) -> Option<VecDeque<Position>> {
+        BfsIter::new(start, |p| self.passable_neighbors::<D, _>(*p, &passable))
+            .find_goal(|p| *p == goal)
+            .map(|(path, _)| path)
+    }
+}
+
+// Quarter turns are applied clockwise after the optional left-right reflection.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct Orientation {
+    pub quarter_turns: usize,
+    pub reflected: bool,
+}
+
+impl Orientation {
+    pub fn all() -> impl Iterator<Item = Orientation> {
+        [false, true].into_iter().flat_map(|reflected| {
+            (0..4).map(move |quarter_turns| Orientation {
+                quarter_turns,
+                reflected,
+            })
+        })
+    }
+
+    pub fn rotations() -> impl Iterator<Item = Orientation> {
+        Self::all().filter(|o| !o.reflected)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PatternMatch {
+    pub position: Position,
+    pub orientation: Orientation,
+}
+
+impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
+    pub fn rotated_clockwise(&self) -> Self {
+        let map = self
+            .map
+            .iter()
+            .map(|(p, v)| (Position::from((self.height as isize - 1 - p[1], p[0])), *v))
+            .collect();
+        Self {
+            map,
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    pub fn reflected(&self) -> Self {
+        let map = self
+            .map
+            .iter()
+            .map(|(p, v)| (Position::from((self.width as isize - 1 - p[0], p[1])), *v))
+            .collect();
+        Self {
+            map,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let mut result = if orientation.reflected {
+            self.reflected()
+        } else {
+            self.clone()
+        };
+        for _ in 0..orientation.quarter_turns % 4 {
+            result = result.rotated_clockwise();
+        }
+        result
+    }
+
+    // Every template cell must lie on the grid, wildcards included.
+    pub fn matches_at(&self, p: Position, template: &GridWorld<V>, wildcard: Option<V>) -> bool {
+        template.position_value_iter().all(|(offset, t)| {
+            self.value(p + *offset)
+                .is_some_and(|v| Some(*t) == wildcard || v == *t)
+        })
+    }
+
+    // Finds every upper-left position where `template` fits, treating `wildcard`
+    // cells as matching anything. Orientations that yield an identical template
+    // are reported only once, under the first such orientation.
+    pub fn find_pattern<I: Iterator<Item = Orientation>>(
+        &self,
+        template: &GridWorld<V>,
+        wildcard: Option<V>,
+        orientations: I,
+    ) -> Vec<PatternMatch> {
+        let mut templates: Vec<(Orientation, GridWorld<V>)> = vec![];
+        for orientation in orientations {
+            let candidate = template.oriented(orientation);
+            if templates.iter().all(|(_, t)| *t != candidate) {
+                templates.push((orientation, candidate));
+            }
+        }
+        let mut result = vec![];
+        for (orientation, t) in templates.iter() {
+            for p in self.position_iter() {
+                if self.matches_at(p, t, wildcard) {
+                    result.push(PatternMatch {
+                        position: p,
+                        orientation: *orientation,
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    // Every start position and direction along which `word` can be read.
+    pub fn find_word<D: DirType>(&self, word: &[V]) -> Vec<(Position, D)> {
+        self.position_iter()
+            .flat_map(|p| all::<D>().map(move |d| (p, d)))
+            .filter(|(p, d)| self.values_from(*p, *d, word.len()) == word)
+            .collect()
+    }
+}
+
+// A maze with its corridors collapsed: nodes are the open cells that are not
+// simply part of a corridor, and each edge weight is the number of steps along
+// the corridor joining its endpoints.
+#[derive(Debug, Clone)]
+pub struct JunctionGraph {
+    graph: WeightedGraph<Position, usize>,
+    corridors: HashMap<(Position, Position), Vec<Position>>,
+}
+
+impl JunctionGraph {
+    pub fn graph(&self) -> &WeightedGraph<Position, usize> {
+        &self.graph
+    }
+
+    // Every cell from `start` to `end` inclusive, if they are joined directly.
+    pub fn corridor(&self, start: Position, end: Position) -> Option<&Vec<Position>> {
+        self.corridors.get(&(start, end))
+    }
+
+    // Converts a path through junctions into the full path of grid cells.
+    pub fn expand_path(&self, junctions: &[Position]) -> Vec<Position> {
+        let mut result = junctions.first().copied().into_iter().collect::<Vec<_>>();
+        for (a, b) in junctions.iter().zip(junctions.iter().skip(1)) {
+            result.extend(self.corridor(*a, *b).unwrap().iter().skip(1));
+        }
+        result
+    }
+}
+
+impl<V: Copy + Clone + Eq + PartialEq> GridWorld<V> {
+    // Junctions are passable cells that do not have exactly two passable
+    // neighbors, along with every position in `keep`. Corridors that loop
+    // without reaching a junction are omitted. Of several corridors joining
+    // the same junctions, only the shortest is kept.
+    pub fn junction_graph<D: DirType, P: Fn(V) -> bool>(
+        &self,
+        passable: P,
+        keep: &BTreeSet<Position>,
+    ) -> JunctionGraph {
+        let open = |p: &Position| self.value(*p).is_some_and(&passable);
+        let is_junction = |p: &Position| {
+            keep.contains(p) || self.passable_neighbors::<D, _>(*p, &passable).len() != 2
+        };
+        let mut result = JunctionGraph {
+            graph: WeightedGraph::default(),
+            corridors: HashMap::new(),
+        };
+        for junction in self.position_iter().filter(|p| open(p) && is_junction(p)) {
+            result.graph.add_node(junction);
+            for first in self.passable_neighbors::<D, _>(junction, &passable) {
+                let mut corridor = vec![junction, first];
+                while !is_junction(corridor.last().unwrap()) {
+                    let current = corridor[corridor.len() - 1];
+                    let previous = corridor[corridor.len() - 2];
+                    let next = self
+                        .passable_neighbors::<D, _>(current, &passable)
+                        .into_iter()
+                        .find(|n| *n != previous)
+                        .unwrap();
+                    corridor.push(next);
+                }
+                let end = *corridor.last().unwrap();
+                let steps = corridor.len() - 1;
+                if end != junction
+                    && result
+                        .graph
+                        .weight(&junction, &end)
+                        .is_none_or(|w| steps < w)
+                {
+                    result.graph.connect(junction, end, steps);
+                    result.corridors.insert((junction, end), corridor);
+                }
+            }
+        }
+        result
+    }
 }
 
 impl<V: CharDisplay + Copy + Eq + PartialEq> Display for GridWorld<V> {
@@ -187,31 +446,58 @@ impl<V: CharDisplay + Copy + Eq + PartialEq> Display for GridWorld<V> {
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct InfiniteGrid<V: Copy + Clone + Debug + Default + Display> {
+trait_set! {
+    pub trait InfiniteGridValue = Copy + Clone + Debug + Default + Display + PartialEq
+}
+
+// Cells holding the default value are not stored, so the bounds always
+// enclose exactly the non-default cells.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InfiniteGrid<V: InfiniteGridValue> {
     map: BTreeMap<Position, V>,
+    x_counts: BTreeMap<isize, usize>,
+    y_counts: BTreeMap<isize, usize>,
 }
 
-impl<V: Copy + Clone + Debug + Default + Display> Display for InfiniteGrid<V> {
+impl<V: InfiniteGridValue> Display for InfiniteGrid<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ((x_start, y_start), (x_end, y_end)) = self.bounding_box();
-        for y in y_start..=y_end {
-            for x in x_start..=x_end {
-                write!(f, "{}", self.get(x, y))?;
+        if let Some(((x_start, y_start), (x_end, y_end))) = self.bounding_box() {
+            for y in y_start..=y_end {
+                for x in x_start..=x_end {
+                    write!(f, "{}", self.get(x, y))?;
+                }
+                write!(f, "\n")?
             }
-            write!(f, "\n")?
         }
         Ok(())
     }
 }
 
-impl<V: Copy + Clone + Debug + Default + Display> InfiniteGrid<V> {
+impl<V: InfiniteGridValue> InfiniteGrid<V> {
     pub fn get_pos(&self, p: Position) -> V {
         self.map.get(&p).copied().unwrap_or_default()
     }
 
     pub fn add_pos(&mut self, p: Position, value: V) {
-        self.map.insert(p, value);
+        if value == V::default() {
+            self.remove_pos(p);
+        } else if self.map.insert(p, value).is_none() {
+            *self.x_counts.entry(p[0]).or_insert(0) += 1;
+            *self.y_counts.entry(p[1]).or_insert(0) += 1;
+        }
+    }
+
+    pub fn remove_pos(&mut self, p: Position) -> Option<V> {
+        let removed = self.map.remove(&p);
+        if removed.is_some() {
+            decrement_count(&mut self.x_counts, p[0]);
+            decrement_count(&mut self.y_counts, p[1]);
+        }
+        removed
+    }
+
+    pub fn contains_pos(&self, p: Position) -> bool {
+        self.map.contains_key(&p)
     }
 
     pub fn get(&self, x: isize, y: isize) -> V {
@@ -222,37 +508,113 @@ impl<V: Copy + Clone + Debug + Default + Display> InfiniteGrid<V> {
         self.add_pos(Position::from((x, y)), value)
     }
 
+    pub fn remove(&mut self, x: isize, y: isize) -> Option<V> {
+        self.remove_pos(Position::from((x, y)))
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        self.contains_pos(Position::from((x, y)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Position, &V)> {
+        self.map.iter()
+    }
+
+    pub fn neighbor_values<D: DirType>(&self, p: Position) -> Vec<V> {
+        all::<D>().map(|d| self.get_pos(d.neighbor(p))).collect()
+    }
+
+    pub fn occupied_neighbors<D: DirType>(&self, p: Position) -> Vec<Position> {
+        all::<D>()
+            .map(|d| d.neighbor(p))
+            .filter(|n| self.contains_pos(*n))
+            .collect()
+    }
+
     pub fn move_square(&mut self, start: (isize, isize), movement: (isize, isize)) {
         let start = Position::from(start);
         let offset = Position::from(movement);
-        let value = self.map.remove(&start).unwrap_or_default();
+        let value = self.remove_pos(start).unwrap_or_default();
         self.add_pos(start + offset, value);
     }
 
-    pub fn bounding_box(&self) -> ((isize, isize), (isize, isize)) {
-        ((self.min_x(), self.min_y()), (self.max_x(), self.max_y()))
+    pub fn bounding_box(&self) -> Option<((isize, isize), (isize, isize))> {
+        Some((
+            (self.min_x()?, self.min_y()?),
+            (self.max_x()?, self.max_y()?),
+        ))
     }
 
-    pub fn min_x(&self) -> isize {
-        self.map.keys().map(|k| k[0]).min().unwrap()
+    pub fn min_x(&self) -> Option<isize> {
+        self.x_counts.keys().next().copied()
     }
 
-    pub fn max_x(&self) -> isize {
-        self.map.keys().map(|k| k[0]).max().unwrap()
+    pub fn max_x(&self) -> Option<isize> {
+        self.x_counts.keys().next_back().copied()
     }
 
-    pub fn min_y(&self) -> isize {
-        self.map.keys().map(|k| k[1]).min().unwrap()
+    pub fn min_y(&self) -> Option<isize> {
+        self.y_counts.keys().next().copied()
     }
 
-    pub fn max_y(&self) -> isize {
-        self.map.keys().map(|k| k[1]).max().unwrap()
+    pub fn max_y(&self) -> Option<isize> {
+        self.y_counts.keys().next_back().copied()
+    }
+}
+
+fn decrement_count(counts: &mut BTreeMap<isize, usize>, key: isize) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+impl<V: InfiniteGridValue + Eq> InfiniteGrid<V> {
+    // The bounding box is translated so that its upper-left corner is the origin.
+    pub fn to_grid_world(&self) -> GridWorld<V> {
+        match self.bounding_box() {
+            None => GridWorld::new(0, 0, V::default()),
+            Some(((x_start, y_start), (x_end, y_end))) => {
+                let mut result = GridWorld::new(
+                    (x_end - x_start + 1) as usize,
+                    (y_end - y_start + 1) as usize,
+                    V::default(),
+                );
+                let origin = Position::from((x_start, y_start));
+                for (p, v) in self.iter() {
+                    result.update(*p - origin, *v);
+                }
+                result
+            }
+        }
+    }
+}
+
+impl<V: InfiniteGridValue + Eq> From<&GridWorld<V>> for InfiniteGrid<V> {
+    fn from(value: &GridWorld<V>) -> Self {
+        let mut result = Self::default();
+        for (p, v) in value.position_value_iter() {
+            result.add_pos(*p, *v);
+        }
+        result
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GridCharWorld;
+    use crate::multidim::{Dir, ManhattanDir, Position};
+
+    use super::{GridCharWorld, InfiniteGrid, Orientation, PatternMatch};
 
     #[test]
     fn test_grid_read() {
@@ -265,4 +627,202 @@ mod tests {
         let maze = maze_str.parse::<GridCharWorld>().unwrap();
         assert_eq!(maze_str, format!("{maze}").as_str());
     }
+
+    #[test]
+    fn test_flood_fill() {
+        let maze = ".....##
+###.###
+#.....#
+#.#####
+#......"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let start = Position::default();
+        let exit = Position::from((6, 4));
+
+        let reachable =
+            maze.reachable_from::<ManhattanDir, _, _>([start].into_iter(), |v| v == '.');
+        assert_eq!(reachable.len(), maze.positions_for('.').len());
+
+        let distances =
+            maze.distances_from::<ManhattanDir, _, _>([start].into_iter(), |v| v == '.');
+        assert_eq!(distances.value(start), Some(Some(0)));
+        assert_eq!(distances.value(exit), Some(Some(14)));
+        assert_eq!(distances.value(Position::from((0, 1))), Some(None));
+
+        let path = maze
+            .shortest_path::<ManhattanDir, _>(start, exit, |v| v == '.')
+            .unwrap();
+        assert_eq!(path.len(), 15);
+        assert_eq!(path.front(), Some(&start));
+        assert_eq!(path.back(), Some(&exit));
+
+        let blocked =
+            maze.shortest_path::<ManhattanDir, _>(start, Position::from((0, 2)), |v| v == '.');
+        assert!(blocked.is_none());
+
+        let diagonal = maze.distances_from::<Dir, _, _>([start].into_iter(), |v| v == '.');
+        assert_eq!(diagonal.value(exit), Some(Some(10)));
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let world = "MMS
+.A.
+MXS
+.A.
+SMM"
+        .parse::<GridCharWorld>()
+        .unwrap();
+        let x_mas = "M.S
+.A.
+M.S"
+        .parse::<GridCharWorld>()
+        .unwrap();
+        let found = world.find_pattern(&x_mas, Some('.'), Orientation::rotations());
+        assert_eq!(
+            found,
+            vec![PatternMatch {
+                position: Position::default(),
+                orientation: Orientation::default()
+            }]
+        );
+
+        let found = world.find_pattern(&x_mas, Some('.'), Orientation::all());
+        assert_eq!(found.len(), 1);
+
+        let corner = "SX".parse::<GridCharWorld>().unwrap();
+        let found = world.find_pattern(&corner, None, Orientation::all());
+        let positions = found.iter().map(|m| m.position).collect::<Vec<_>>();
+        assert_eq!(positions, vec![Position::from((1, 2))]);
+        assert_eq!(found[0].orientation.quarter_turns, 2);
+
+        // The wildcard column would hang off the right edge at x = 2.
+        let s_then_any = "S.".parse::<GridCharWorld>().unwrap();
+        assert!(!world.matches_at(Position::from((2, 0)), &s_then_any, Some('.')));
+        let found =
+            world.find_pattern(&s_then_any, Some('.'), [Orientation::default()].into_iter());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].position, Position::from((0, 4)));
+    }
+
+    #[test]
+    fn test_find_word() {
+        let world = "XMAS
+M..A
+A..M
+SAMX"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let found = world.find_word::<Dir>(&['X', 'M', 'A', 'S']);
+        assert_eq!(
+            found,
+            vec![
+                (Position::from((0, 0)), Dir::E),
+                (Position::from((0, 0)), Dir::S),
+                (Position::from((3, 3)), Dir::N),
+                (Position::from((3, 3)), Dir::W),
+            ]
+        );
+        assert!(world.find_word::<ManhattanDir>(&['X', 'A']).is_empty());
+    }
+
+    #[test]
+    fn test_infinite_grid() {
+        let mut grid = InfiniteGrid::<usize>::default();
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounding_box(), None);
+        assert_eq!(format!("{grid}"), "");
+
+        grid.add(-2, 3, 1);
+        grid.add(4, -1, 2);
+        grid.add(1, 1, 3);
+        assert_eq!(grid.bounding_box(), Some(((-2, -1), (4, 3))));
+        assert!(grid.contains(1, 1));
+        assert_eq!(
+            grid.neighbor_values::<ManhattanDir>(Position::from((1, 0))),
+            vec![0, 0, 3, 0]
+        );
+        assert_eq!(
+            grid.occupied_neighbors::<Dir>(Position::from((0, 2))),
+            vec![Position::from((1, 1))]
+        );
+
+        assert_eq!(grid.remove(4, -1), Some(2));
+        assert_eq!(grid.bounding_box(), Some(((-2, 1), (1, 3))));
+        grid.add(-2, 3, 0);
+        assert!(!grid.contains(-2, 3));
+        assert_eq!(grid.bounding_box(), Some(((1, 1), (1, 1))));
+        grid.move_square((1, 1), (0, 2));
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            vec![(&Position::from((1, 3)), &3)]
+        );
+        grid.remove(1, 3);
+        assert_eq!(grid.bounding_box(), None);
+    }
+
+    #[test]
+    fn test_infinite_grid_conversion() {
+        let world = "..#
+#..
+.#."
+        .parse::<GridCharWorld>()
+        .unwrap();
+        let sparse = world
+            .position_value_iter()
+            .filter(|(_, v)| **v == '#')
+            .map(|(p, _)| (*p + Position::from((5, -5)), 1))
+            .fold(InfiniteGrid::<u8>::default(), |mut g, (p, v)| {
+                g.add_pos(p, v);
+                g
+            });
+        assert_eq!(format!("{sparse}"), "001\n100\n010\n");
+        let dense = sparse.to_grid_world();
+        assert_eq!(dense.width(), 3);
+        assert_eq!(dense.value(Position::from((2, 0))), Some(1));
+        assert_eq!(InfiniteGrid::from(&dense).len(), 3);
+
+        let chars = InfiniteGrid::from(&world);
+        assert_eq!(chars.len(), 9);
+        assert_eq!(chars.to_grid_world(), world);
+    }
+
+    #[test]
+    fn test_junction_graph() {
+        let maze = "#########
+#S....#.#
+#.###.#.#
+#.#...#.#
+#.#.###.#
+#.......#
+#.###.#.#
+#...#..E#
+#########"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let start = maze.any_position_for('S');
+        let end = maze.any_position_for('E');
+        let open = |v| v != '#';
+        let junctions = maze.junction_graph::<ManhattanDir, _>(open, &[start, end].into());
+        let graph = junctions.graph();
+        assert!(graph.len() < maze.len() - maze.positions_for('#').len());
+
+        let paths = graph.dijkstra(&start);
+        let grid_path = maze
+            .shortest_path::<ManhattanDir, _>(start, end, open)
+            .unwrap();
+        assert_eq!(paths.cost_for(&end), grid_path.len() - 1);
+
+        let mut junction_path = paths.path_back_from(&end);
+        junction_path.make_contiguous().reverse();
+        let expanded = junctions.expand_path(junction_path.make_contiguous());
+        assert_eq!(expanded.len(), grid_path.len());
+        assert_eq!(expanded.first(), Some(&start));
+        assert_eq!(expanded.last(), Some(&end));
+        assert!(expanded
+            .iter()
+            .zip(expanded.iter().skip(1))
+            .all(|(a, b)| a.manhattan_distance(b) == 1 && open(maze.value(*b).unwrap())));
+    }
 }
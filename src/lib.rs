@@ -1,7 +1,12 @@
+pub mod automaton;
+pub mod cycles;
 pub mod extended_euclid;
 pub mod graph;
 pub mod grid;
+pub mod memo;
 pub mod multidim;
+pub mod parallel;
+pub mod render;
 pub mod search_iter;
 
 use std::{
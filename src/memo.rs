@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Add,
+};
+
+use num::{One, Zero};
+
+use crate::search_iter::SearchNode;
+
+// A cache for recursive subproblems. The closure passed to `compute` receives
+// a key and a function for solving subproblems through the same cache.
+#[derive(Clone, Debug)]
+pub struct Memo<K: SearchNode, V: Clone> {
+    cache: HashMap<K, V>,
+    // Keys whose computation has started but not finished, in call order.
+    stack: Vec<K>,
+    in_progress: HashSet<K>,
+}
+
+impl<K: SearchNode, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: HashMap::new(),
+            stack: vec![],
+            in_progress: HashSet::new(),
+        }
+    }
+}
+
+impl<K: SearchNode, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    // Panics if the recursion reaches a key that depends on itself.
+    pub fn compute<F: Fn(&K, &mut dyn FnMut(&K) -> V) -> V>(&mut self, key: &K, f: &F) -> V {
+        if let Some(value) = self.cache.get(key) {
+            return value.clone();
+        }
+        if let Some(cycle) = self.cycle_at(key) {
+            panic!("Memo::compute: cycle {cycle:?}");
+        }
+        self.enter(key);
+        let value = f(key, &mut |sub| self.compute(sub, f));
+        self.exit(key, value)
+    }
+
+    // Like `compute`, but a key that depends on itself produces an `Err`
+    // holding the cycle, starting and ending just before that key recurs.
+    pub fn try_compute<F>(&mut self, key: &K, f: &F) -> Result<V, Vec<K>>
+    where
+        F: Fn(&K, &mut dyn FnMut(&K) -> Result<V, Vec<K>>) -> Result<V, Vec<K>>,
+    {
+        if let Some(value) = self.cache.get(key) {
+            return Ok(value.clone());
+        }
+        if let Some(cycle) = self.cycle_at(key) {
+            return Err(cycle);
+        }
+        self.enter(key);
+        match f(key, &mut |sub| self.try_compute(sub, f)) {
+            Ok(value) => Ok(self.exit(key, value)),
+            Err(cycle) => {
+                self.stack.pop();
+                self.in_progress.remove(key);
+                Err(cycle)
+            }
+        }
+    }
+
+    fn cycle_at(&self, key: &K) -> Option<Vec<K>> {
+        self.in_progress.contains(key).then(|| {
+            let start = self.stack.iter().position(|k| k == key).unwrap();
+            self.stack[start..].to_vec()
+        })
+    }
+
+    fn enter(&mut self, key: &K) {
+        self.stack.push(key.clone());
+        self.in_progress.insert(key.clone());
+    }
+
+    fn exit(&mut self, key: &K, value: V) -> V {
+        self.stack.pop();
+        self.in_progress.remove(key);
+        self.cache.insert(key.clone(), value.clone());
+        value
+    }
+}
+
+impl<K: SearchNode, C: Zero + One + Add<Output = C> + Clone> Memo<K, C> {
+    // Counts the paths from `start` that end at a goal, in the DAG given by
+    // `successor`. Paths may continue through a goal to reach later ones.
+    // Counts are cached, so one `Memo` can serve many starts over the same DAG.
+    pub fn count_paths<S: Fn(&K) -> Vec<K>, G: Fn(&K) -> bool>(
+        &mut self,
+        start: &K,
+        successor: S,
+        is_goal: G,
+    ) -> Result<C, Vec<K>> {
+        self.try_compute(start, &|node, count| {
+            let mut total = if is_goal(node) { C::one() } else { C::zero() };
+            for next in successor(node) {
+                total = total + count(&next)?;
+            }
+            Ok(total)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Memo;
+
+    #[test]
+    fn test_fibonacci() {
+        let mut memo = Memo::new();
+        let fib = |n: &u64, fib: &mut dyn FnMut(&u64) -> u128| {
+            if *n < 2 {
+                *n as u128
+            } else {
+                fib(&(n - 1)) + fib(&(n - 2))
+            }
+        };
+        assert_eq!(memo.compute(&90, &fib), 2880067194370816120);
+        assert_eq!(memo.len(), 91);
+        assert_eq!(memo.get(&10), Some(&55));
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        // 0 -> 1 -> 2 -> 3 -> 1
+        let next = [vec![1], vec![2], vec![3], vec![1]];
+        let mut memo = Memo::<usize, usize>::new();
+        let depth = |n: &usize, depth: &mut dyn FnMut(&usize) -> Result<usize, Vec<usize>>| {
+            let mut deepest = 0;
+            for m in next[*n].iter() {
+                deepest = deepest.max(1 + depth(m)?);
+            }
+            Ok(deepest)
+        };
+        assert_eq!(memo.try_compute(&0, &depth), Err(vec![1, 2, 3]));
+        assert!(memo.is_empty());
+        assert_eq!(memo.try_compute(&3, &depth), Err(vec![3, 1, 2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn test_compute_panics_on_cycle() {
+        let mut memo = Memo::<usize, usize>::new();
+        memo.compute(&0, &|n, f| f(&((n + 1) % 3)));
+    }
+
+    #[test]
+    fn test_count_paths() {
+        // Monotone lattice paths across an 8x8 grid: C(16, 8).
+        let mut memo = Memo::<(usize, usize), u64>::new();
+        let successor = |(x, y): &(usize, usize)| {
+            let mut result = vec![];
+            if *x < 8 {
+                result.push((x + 1, *y));
+            }
+            if *y < 8 {
+                result.push((*x, y + 1));
+            }
+            result
+        };
+        let count = memo.count_paths(&(0, 0), successor, |p| *p == (8, 8));
+        assert_eq!(count, Ok(12870));
+        assert_eq!(
+            memo.count_paths(&(4, 4), successor, |p| *p == (8, 8)),
+            Ok(70)
+        );
+
+        let mut memo = Memo::<usize, u64>::new();
+        let looping = memo.count_paths(&0, |n| vec![(n + 1) % 4], |n| *n == 2);
+        assert_eq!(looping, Err(vec![0, 1, 2, 3]));
+    }
+}
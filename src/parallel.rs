@@ -0,0 +1,114 @@
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+// One worker per available core.
+pub fn num_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+// Applies `f` to every item on scoped threads, keeping the results in order.
+pub fn parallel_map<T: Sync, U: Send, F: Fn(&T) -> U + Sync>(items: &[T], f: F) -> Vec<U> {
+    let chunk_size = items.len().div_ceil(num_threads()).max(1);
+    thread::scope(|scope| {
+        let workers = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(&f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+pub fn parallel_count<T: Sync, P: Fn(&T) -> bool + Sync>(items: &[T], predicate: P) -> usize {
+    parallel_map(items, predicate)
+        .into_iter()
+        .filter(|b| *b)
+        .count()
+}
+
+// Returns some item satisfying `predicate`, not necessarily the first. Workers
+// take items one at a time and stop once any of them succeeds, which suits
+// expensive predicates of uneven cost.
+pub fn parallel_find_any<T: Sync, P: Fn(&T) -> bool + Sync>(
+    items: &[T],
+    predicate: P,
+) -> Option<&T> {
+    parallel_find_any_stoppable(items, |item, _| predicate(item))
+}
+
+// Like `parallel_find_any`, but `predicate` also receives the flag that is set
+// once an item is found. Long-running predicates should poll it and give up
+// early, since a worker only checks it between items.
+pub fn parallel_find_any_stoppable<T: Sync, P: Fn(&T, &AtomicBool) -> bool + Sync>(
+    items: &[T],
+    predicate: P,
+) -> Option<&T> {
+    let next = AtomicUsize::new(0);
+    let found = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let workers = (0..num_threads())
+            .map(|_| {
+                scope.spawn(|| {
+                    while !found.load(Ordering::Relaxed) {
+                        let item = items.get(next.fetch_add(1, Ordering::Relaxed))?;
+                        if predicate(item, &found) {
+                            found.store(true, Ordering::Relaxed);
+                            return Some(item);
+                        }
+                    }
+                    None
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .next()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::{parallel_count, parallel_find_any, parallel_find_any_stoppable, parallel_map};
+
+    #[test]
+    fn test_parallel_map() {
+        let items = (0..1000u64).collect::<Vec<_>>();
+        let squares = parallel_map(&items, |n| n * n);
+        assert_eq!(squares, items.iter().map(|n| n * n).collect::<Vec<_>>());
+        assert!(parallel_map(&[] as &[u64], |n| *n).is_empty());
+        assert_eq!(parallel_count(&items, |n| n % 7 == 3), 143);
+    }
+
+    #[test]
+    fn test_parallel_find_any() {
+        let items = (0..10_000u64).collect::<Vec<_>>();
+        let found = parallel_find_any(&items, |n| n % 1000 == 999).unwrap();
+        assert_eq!(found % 1000, 999);
+        assert_eq!(parallel_find_any(&items, |n| *n > 20_000), None);
+    }
+
+    #[test]
+    fn test_parallel_find_any_stoppable() {
+        // Every item but 0 spins until told to stop, so this only finishes if
+        // the workers holding those items see the flag.
+        let items = (0..64u64).collect::<Vec<_>>();
+        let found = parallel_find_any_stoppable(&items, |n, stop| {
+            if *n == 0 {
+                return true;
+            }
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::yield_now();
+            }
+            false
+        });
+        assert_eq!(found, Some(&0));
+    }
+}
@@ -0,0 +1,291 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::{
+    grid::{CharDisplay, GridWorld},
+    multidim::Position,
+};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn ansi_foreground(&self) -> u8 {
+        30 + *self as u8
+    }
+
+    fn ansi_background(&self) -> u8 {
+        self.ansi_foreground() + 10
+    }
+}
+
+// Unset fields leave whatever lies underneath unchanged.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub struct Style {
+    pub glyph: Option<char>,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Style {
+    pub fn glyph(glyph: char) -> Self {
+        Self {
+            glyph: Some(glyph),
+            ..Self::default()
+        }
+    }
+
+    pub fn color(foreground: Color) -> Self {
+        Self {
+            foreground: Some(foreground),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_foreground(mut self, foreground: Color) -> Self {
+        self.foreground = Some(foreground);
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    fn layered(&self, top: &Style) -> Style {
+        Style {
+            glyph: top.glyph.or(self.glyph),
+            foreground: top.foreground.or(self.foreground),
+            background: top.background.or(self.background),
+        }
+    }
+}
+
+// Renders a grid with any number of overlays on top. Overlays added later
+// are drawn over earlier ones, and all of them over the per-cell styles.
+pub struct GridRenderer<'a, V> {
+    grid: &'a GridWorld<V>,
+    cell_styles: Option<Box<dyn Fn(Position, V) -> Style + 'a>>,
+    overlays: Vec<(BTreeSet<Position>, Style)>,
+    viewport: Option<(Position, Position)>,
+    ansi: bool,
+}
+
+impl<'a, V: CharDisplay + Copy + Eq + PartialEq> GridRenderer<'a, V> {
+    pub fn new(grid: &'a GridWorld<V>) -> Self {
+        Self {
+            grid,
+            cell_styles: None,
+            overlays: vec![],
+            viewport: None,
+            ansi: false,
+        }
+    }
+
+    pub fn with_overlay<I: IntoIterator<Item = Position>>(
+        mut self,
+        positions: I,
+        style: Style,
+    ) -> Self {
+        self.overlays.push((positions.into_iter().collect(), style));
+        self
+    }
+
+    pub fn with_cell_styles<F: Fn(Position, V) -> Style + 'a>(mut self, styler: F) -> Self {
+        self.cell_styles = Some(Box::new(styler));
+        self
+    }
+
+    // Only positions between the two corners, inclusive, are rendered.
+    pub fn cropped(mut self, upper_left: Position, lower_right: Position) -> Self {
+        self.viewport = Some((upper_left, lower_right));
+        self
+    }
+
+    pub fn with_ansi(mut self) -> Self {
+        self.ansi = true;
+        self
+    }
+
+    pub fn style_at(&self, p: Position) -> Option<Style> {
+        self.grid.value(p).map(|v| {
+            let base = Style::glyph(v.display());
+            let mut style = self
+                .cell_styles
+                .as_ref()
+                .map_or(base, |styler| base.layered(&styler(p, v)));
+            for (positions, overlay) in self.overlays.iter() {
+                if positions.contains(&p) {
+                    style = style.layered(overlay);
+                }
+            }
+            style
+        })
+    }
+
+    pub fn glyph_at(&self, p: Position) -> Option<char> {
+        self.style_at(p).and_then(|s| s.glyph)
+    }
+
+    fn rows(&self) -> Vec<Vec<Position>> {
+        let (mut upper_left, mut lower_right) = (
+            Position::default(),
+            Position::from((
+                self.grid.width() as isize - 1,
+                self.grid.height() as isize - 1,
+            )),
+        );
+        if let Some((ul, lr)) = self.viewport {
+            upper_left = Position::from((ul[0].max(upper_left[0]), ul[1].max(upper_left[1])));
+            lower_right = Position::from((lr[0].min(lower_right[0]), lr[1].min(lower_right[1])));
+        }
+        if upper_left[0] > lower_right[0] || upper_left[1] > lower_right[1] {
+            return vec![];
+        }
+        (upper_left[1]..=lower_right[1])
+            .map(|y| {
+                (upper_left[0]..=lower_right[0])
+                    .map(|x| Position::from((x, y)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Plain PBM (P1): cells whose rendered glyph satisfies `is_dark` are black.
+    pub fn pbm<F: Fn(char) -> bool>(&self, is_dark: F) -> String {
+        self.netpbm("P1", None, |c| if is_dark(c) { 1 } else { 0 })
+    }
+
+    // Plain PGM (P2) with the given maximum gray level.
+    pub fn pgm<F: Fn(char) -> u16>(&self, max_gray: u16, shade: F) -> String {
+        self.netpbm("P2", Some(max_gray), shade)
+    }
+
+    pub fn save_pbm<F: Fn(char) -> bool>(&self, filename: &str, is_dark: F) -> anyhow::Result<()> {
+        std::fs::write(filename, self.pbm(is_dark))?;
+        Ok(())
+    }
+
+    pub fn save_pgm<F: Fn(char) -> u16>(
+        &self,
+        filename: &str,
+        max_gray: u16,
+        shade: F,
+    ) -> anyhow::Result<()> {
+        std::fs::write(filename, self.pgm(max_gray, shade))?;
+        Ok(())
+    }
+
+    fn netpbm<F: Fn(char) -> u16>(&self, magic: &str, max_value: Option<u16>, pixel: F) -> String {
+        let rows = self.rows();
+        let width = rows.first().map_or(0, |r| r.len());
+        let mut result = format!("{magic}\n{width} {}\n", rows.len());
+        if let Some(max_value) = max_value {
+            result.push_str(format!("{max_value}\n").as_str());
+        }
+        for row in rows {
+            let values = row
+                .iter()
+                .map(|p| pixel(self.glyph_at(*p).unwrap()).to_string())
+                .collect::<Vec<_>>();
+            result.push_str(values.join(" ").as_str());
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<'a, V: CharDisplay + Copy + Eq + PartialEq> Display for GridRenderer<'a, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.rows().iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for p in row.iter() {
+                let style = self.style_at(*p).unwrap();
+                let glyph = style.glyph.unwrap();
+                let mut codes = vec![];
+                if self.ansi {
+                    codes.extend(style.foreground.map(|c| c.ansi_foreground()));
+                    codes.extend(style.background.map(|c| c.ansi_background()));
+                }
+                if codes.is_empty() {
+                    write!(f, "{glyph}")?;
+                } else {
+                    let codes = codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+                    write!(f, "\x1b[{}m{glyph}\x1b[0m", codes.join(";"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::GridCharWorld, multidim::Position};
+
+    use super::{Color, GridRenderer, Style};
+
+    #[test]
+    fn test_overlays() {
+        let maze = "#...#
+#.#.#
+#...#"
+            .parse::<GridCharWorld>()
+            .unwrap();
+        let path = [(1, 0), (1, 1), (1, 2), (2, 2)].map(Position::from);
+        let renderer = GridRenderer::new(&maze)
+            .with_overlay(path, Style::glyph('O'))
+            .with_overlay([Position::from((2, 2))], Style::glyph('E'));
+        assert_eq!(
+            format!("{renderer}"),
+            "#O..#
+#O#.#
+#OE.#"
+        );
+
+        let cropped =
+            GridRenderer::new(&maze).cropped(Position::from((1, 1)), Position::from((9, 9)));
+        assert_eq!(format!("{cropped}"), ".#.#\n...#");
+
+        let beside =
+            GridRenderer::new(&maze).cropped(Position::from((7, 0)), Position::from((9, 2)));
+        assert_eq!(format!("{beside}"), "");
+        assert_eq!(beside.pbm(|c| c == '#'), "P1\n0 0\n");
+    }
+
+    #[test]
+    fn test_ansi() {
+        let maze = "#.".parse::<GridCharWorld>().unwrap();
+        let renderer = GridRenderer::new(&maze)
+            .with_cell_styles(|_, v| {
+                if v == '#' {
+                    Style::color(Color::Red).with_background(Color::White)
+                } else {
+                    Style::default()
+                }
+            })
+            .with_ansi();
+        assert_eq!(format!("{renderer}"), "\x1b[31;47m#\x1b[0m.");
+    }
+
+    #[test]
+    fn test_netpbm() {
+        let image = "O.\n.O\nOO".parse::<GridCharWorld>().unwrap();
+        let renderer = GridRenderer::new(&image);
+        assert_eq!(renderer.pbm(|c| c == 'O'), "P1\n2 3\n1 0\n0 1\n1 1\n");
+        assert_eq!(
+            renderer.pgm(9, |c| if c == 'O' { 0 } else { 9 }),
+            "P2\n2 3\n9\n0 9\n9 0\n0 0\n"
+        );
+    }
+}
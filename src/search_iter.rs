@@ -1,12 +1,15 @@
 use common_macros::hash_map;
-use num::Integer;
+use num::Zero;
 use priority_queue::PriorityQueue;
-use std::collections::{HashMap, VecDeque};
-use std::fmt::Debug;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::ops::Add;
 use trait_set::trait_set;
 
+use crate::parallel::parallel_map;
+
 trait_set! {
     pub trait SearchNode = Clone + Hash + Eq + Debug;
 }
@@ -16,6 +19,9 @@ pub struct BfsIter<T: SearchNode, S: FnMut(&T) -> Vec<T>> {
     depths: HashMap<T, usize>,
     parents: HashMap<T, Option<T>>,
     successor: S,
+    max_depth: Option<usize>,
+    budget: Option<usize>,
+    num_yielded: usize,
 }
 
 impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> BfsIter<T, S> {
@@ -27,6 +33,9 @@ impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> BfsIter<T, S> {
             depths: hash_map!(start.clone() => 0),
             successor,
             parents: hash_map!(start.clone() => None),
+            max_depth: None,
+            budget: None,
+            num_yielded: 0,
         }
     }
 
@@ -39,17 +48,58 @@ impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> BfsIter<T, S> {
             depths,
             successor,
             parents,
+            max_depth: None,
+            budget: None,
+            num_yielded: 0,
         }
     }
 
+    // Nodes deeper than `max_depth` are never discovered.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // Iteration stops once `budget` nodes have been yielded.
+    pub fn with_node_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    // True when the node budget ended the search with nodes still queued.
+    pub fn budget_exhausted(&self) -> bool {
+        self.budget == Some(self.num_yielded) && !self.queue.is_empty()
+    }
+
+    // Continues the search until a node satisfies `is_goal`, returning that
+    // node's path from the start and its depth.
+    pub fn find_goal<G: Fn(&T) -> bool>(&mut self, is_goal: G) -> Option<(VecDeque<T>, usize)> {
+        let goal = self.find(|n| is_goal(n))?;
+        let depth = self.depths[&goal];
+        self.path_to(&goal).map(|path| (path, depth))
+    }
+
     pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
         path_back_from(node, &self.parents)
     }
 
+    // From the start to `node`, or None if `node` has not been discovered.
+    pub fn path_to(&self, node: &T) -> Option<VecDeque<T>> {
+        self.parents.contains_key(node).then(|| {
+            let mut path = self.path_back_from(node);
+            path.make_contiguous().reverse();
+            path
+        })
+    }
+
     pub fn depth_for(&self, node: &T) -> usize {
         self.depths.get(node).copied().unwrap()
     }
 
+    pub fn depth_of(&self, node: &T) -> Option<usize> {
+        self.depths.get(node).copied()
+    }
+
     pub fn all_depths(&self) -> HashMap<T, usize> {
         self.depths.clone()
     }
@@ -59,7 +109,14 @@ impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> Iterator for BfsIter<T, S> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.budget.is_some_and(|budget| self.num_yielded >= budget) {
+            return None;
+        }
         self.queue.pop_front().map(|(parent, depth)| {
+            self.num_yielded += 1;
+            if self.max_depth.is_some_and(|max| depth >= max) {
+                return parent;
+            }
             for child in (self.successor)(&parent) {
                 if !self.depths.contains_key(&child) {
                     self.depths.insert(child.clone(), depth + 1);
@@ -72,7 +129,87 @@ impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> Iterator for BfsIter<T, S> {
     }
 }
 
-fn path_back_from<T: SearchNode>(node: &T, parents: &HashMap<T, Option<T>>) -> VecDeque<T> {
+// Breadth-first search that expands each level of the frontier on several
+// threads. Yields a whole level at a time, in the same order as `BfsIter`.
+pub struct ParallelBfsIter<T: SearchNode + Send + Sync, S: Fn(&T) -> Vec<T> + Sync> {
+    frontier: Vec<T>,
+    depth: usize,
+    depths: HashMap<T, usize>,
+    parents: HashMap<T, Option<T>>,
+    successor: S,
+}
+
+impl<T: SearchNode + Send + Sync, S: Fn(&T) -> Vec<T> + Sync> ParallelBfsIter<T, S> {
+    pub fn new(start: T, successor: S) -> Self {
+        Self::multi_start(std::iter::once(start), successor)
+    }
+
+    pub fn multi_start<I: Iterator<Item = T>>(starts: I, successor: S) -> Self {
+        let mut frontier = vec![];
+        let mut parents = HashMap::new();
+        for start in starts {
+            if parents.insert(start.clone(), None).is_none() {
+                frontier.push(start);
+            }
+        }
+        Self {
+            depths: frontier.iter().map(|t| (t.clone(), 0)).collect(),
+            frontier,
+            depth: 0,
+            parents,
+            successor,
+        }
+    }
+
+    pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
+        path_back_from(node, &self.parents)
+    }
+
+    pub fn path_to(&self, node: &T) -> Option<VecDeque<T>> {
+        self.parents.contains_key(node).then(|| {
+            let mut path = self.path_back_from(node);
+            path.make_contiguous().reverse();
+            path
+        })
+    }
+
+    pub fn depth_of(&self, node: &T) -> Option<usize> {
+        self.depths.get(node).copied()
+    }
+
+    pub fn all_depths(&self) -> HashMap<T, usize> {
+        self.depths.clone()
+    }
+}
+
+impl<T: SearchNode + Send + Sync, S: Fn(&T) -> Vec<T> + Sync> Iterator for ParallelBfsIter<T, S> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frontier.is_empty() {
+            return None;
+        }
+        let level = std::mem::take(&mut self.frontier);
+        // Successors are generated in parallel; recording them stays sequential.
+        let children = parallel_map(&level, &self.successor);
+        self.depth += 1;
+        for (parent, children) in level.iter().zip(children) {
+            for child in children {
+                if !self.depths.contains_key(&child) {
+                    self.depths.insert(child.clone(), self.depth);
+                    self.parents.insert(child.clone(), Some(parent.clone()));
+                    self.frontier.push(child);
+                }
+            }
+        }
+        Some(level)
+    }
+}
+
+pub(crate) fn path_back_from<T: SearchNode>(
+    node: &T,
+    parents: &HashMap<T, Option<T>>,
+) -> VecDeque<T> {
     let mut result = VecDeque::new();
     let mut current = node;
     result.push_back(current.clone());
@@ -83,11 +220,318 @@ fn path_back_from<T: SearchNode>(node: &T, parents: &HashMap<T, Option<T>>) -> V
     result
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DfsEvent<T> {
+    // A node is first reached; its descendants follow.
+    Enter(T),
+    // Every descendant of the node has been explored.
+    Exit(T),
+}
+
+pub struct DfsIter<T: SearchNode, S: FnMut(&T) -> Vec<T>> {
+    roots: Vec<T>,
+    // Each entry holds a node and its unexplored children, in reverse order.
+    stack: Vec<(T, Vec<T>)>,
+    depths: HashMap<T, usize>,
+    parents: HashMap<T, Option<T>>,
+    successor: S,
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> DfsIter<T, S> {
+    pub fn new(start: T, successor: S) -> Self {
+        Self::multi_start(std::iter::once(start), successor)
+    }
+
+    pub fn multi_start<I: Iterator<Item = T>>(starts: I, successor: S) -> Self {
+        let mut roots = starts.collect::<Vec<_>>();
+        roots.reverse();
+        Self {
+            roots,
+            stack: vec![],
+            depths: HashMap::new(),
+            parents: HashMap::new(),
+            successor,
+        }
+    }
+
+    // Pre-order and post-order events instead of just pre-order nodes.
+    pub fn events(self) -> DfsEvents<T, S> {
+        DfsEvents { dfs: self }
+    }
+
+    pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
+        path_back_from(node, &self.parents)
+    }
+
+    // Depth within the DFS tree, which need not be the shortest distance.
+    pub fn depth_for(&self, node: &T) -> usize {
+        self.depths.get(node).copied().unwrap()
+    }
+
+    pub fn all_depths(&self) -> HashMap<T, usize> {
+        self.depths.clone()
+    }
+
+    fn enter(&mut self, node: T, parent: Option<T>) -> DfsEvent<T> {
+        let depth = parent.as_ref().map_or(0, |p| self.depths[p] + 1);
+        self.depths.insert(node.clone(), depth);
+        self.parents.insert(node.clone(), parent);
+        let mut children = (self.successor)(&node);
+        children.reverse();
+        self.stack.push((node.clone(), children));
+        DfsEvent::Enter(node)
+    }
+
+    fn next_event(&mut self) -> Option<DfsEvent<T>> {
+        loop {
+            match self.stack.last_mut() {
+                None => {
+                    let root = self.roots.pop()?;
+                    if !self.depths.contains_key(&root) {
+                        return Some(self.enter(root, None));
+                    }
+                }
+                Some((node, children)) => match children.pop() {
+                    Some(child) => {
+                        if !self.depths.contains_key(&child) {
+                            let parent = node.clone();
+                            return Some(self.enter(child, Some(parent)));
+                        }
+                    }
+                    None => {
+                        let (node, _) = self.stack.pop().unwrap();
+                        return Some(DfsEvent::Exit(node));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> Iterator for DfsIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let DfsEvent::Enter(node) = self.next_event()? {
+                return Some(node);
+            }
+        }
+    }
+}
+
+pub struct DfsEvents<T: SearchNode, S: FnMut(&T) -> Vec<T>> {
+    dfs: DfsIter<T, S>,
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> DfsEvents<T, S> {
+    pub fn search(&self) -> &DfsIter<T, S> {
+        &self.dfs
+    }
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> Iterator for DfsEvents<T, S> {
+    type Item = DfsEvent<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dfs.next_event()
+    }
+}
+
+// Yields every simple path from the start, each as a sequence from the start
+// to its final node. Exponential in general, so best suited to sparse graphs.
+pub struct SimplePaths<T: SearchNode, S: FnMut(&T) -> Vec<T>> {
+    start: Option<T>,
+    path: Vec<T>,
+    on_path: HashSet<T>,
+    // Unexplored children of each node on the path, in reverse order.
+    stack: Vec<Vec<T>>,
+    successor: S,
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> SimplePaths<T, S> {
+    pub fn new(start: T, successor: S) -> Self {
+        Self {
+            start: Some(start),
+            path: vec![],
+            on_path: HashSet::new(),
+            stack: vec![],
+            successor,
+        }
+    }
+
+    fn extend(&mut self, node: T) -> Vec<T> {
+        let mut children = (self.successor)(&node);
+        children.reverse();
+        self.stack.push(children);
+        self.on_path.insert(node.clone());
+        self.path.push(node);
+        self.path.clone()
+    }
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<T>> Iterator for SimplePaths<T, S> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            return Some(self.extend(start));
+        }
+        loop {
+            match self.stack.last_mut()?.pop() {
+                Some(child) => {
+                    if !self.on_path.contains(&child) {
+                        return Some(self.extend(child));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    let node = self.path.pop().unwrap();
+                    self.on_path.remove(&node);
+                }
+            }
+        }
+    }
+}
+
+// Iterative deepening DFS: repeats a depth-limited search with limits
+// 0, 1, ..., max_depth. Returns a shortest path from start to a goal, using
+// memory proportional to the path length rather than the search frontier.
+pub fn iddfs<T: SearchNode, S: FnMut(&T) -> Vec<T>, G: Fn(&T) -> bool>(
+    start: T,
+    max_depth: usize,
+    mut successor: S,
+    is_goal: G,
+) -> Option<Vec<T>> {
+    let mut path = vec![start];
+    for limit in 0..=max_depth {
+        match depth_limited(&mut path, limit, &mut successor, &is_goal) {
+            Deepening::Found => return Some(path),
+            Deepening::Exhausted => return None,
+            Deepening::CutOff => {}
+        }
+    }
+    None
+}
+
+enum Deepening {
+    Found,
+    // Some branch reached the depth limit, so a deeper search may succeed.
+    CutOff,
+    Exhausted,
+}
+
+fn depth_limited<T: SearchNode, S: FnMut(&T) -> Vec<T>, G: Fn(&T) -> bool>(
+    path: &mut Vec<T>,
+    limit: usize,
+    successor: &mut S,
+    is_goal: &G,
+) -> Deepening {
+    let node = path.last().unwrap().clone();
+    if is_goal(&node) {
+        return Deepening::Found;
+    }
+    if limit == 0 {
+        return Deepening::CutOff;
+    }
+    let mut result = Deepening::Exhausted;
+    for child in successor(&node) {
+        if !path.contains(&child) {
+            path.push(child);
+            match depth_limited(path, limit - 1, successor, is_goal) {
+                Deepening::Found => return Deepening::Found,
+                Deepening::CutOff => result = Deepening::CutOff,
+                Deepening::Exhausted => {}
+            }
+            path.pop();
+        }
+    }
+    result
+}
+
 trait_set! {
-    pub trait Estimator = Integer + Copy + Clone + Add<Output=Self> + PartialOrd + Ord + Debug + Default
+    pub trait Estimator = Zero + Copy + Clone + Add<Output=Self> + PartialOrd + Ord + Debug + Default
+}
+
+// An `f64` cost ordered by `f64::total_cmp`, so it can be used as an `Estimator`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TotalF64(pub f64);
+
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TotalF64 {}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for TotalF64 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Zero for TotalF64 {
+    fn zero() -> Self {
+        Self(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0.0
+    }
+}
+
+impl Display for TotalF64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// A pair of costs added componentwise and compared by the first, then the second.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lexicographic<A, B>(pub A, pub B);
+
+impl<A: Add<Output = A>, B: Add<Output = B>> Add for Lexicographic<A, B> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl<A: Zero, B: Zero> Zero for Lexicographic<A, B> {
+    fn zero() -> Self {
+        Self(A::zero(), B::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero() && self.1.is_zero()
+    }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Ord)]
+impl<A, B> From<(A, B)> for Lexicographic<A, B> {
+    fn from((a, b): (A, B)) -> Self {
+        Self(a, b)
+    }
+}
+
+// PriorityQueue pops its greatest element, so "greater" means "explore sooner":
+// a smaller total estimate, then (on ties) more progress from the start.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 struct TotalEstimate<N: Estimator> {
     from_start: N,
     estimate_to_goal: N,
@@ -106,9 +550,18 @@ impl<N: Estimator> TotalEstimate<N> {
     }
 }
 
+impl<N: Estimator> Ord for TotalEstimate<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .total()
+            .cmp(&self.total())
+            .then_with(|| self.from_start.cmp(&other.from_start))
+    }
+}
+
 impl<N: Estimator> PartialOrd for TotalEstimate<N> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.total().partial_cmp(&self.total())
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -121,6 +574,8 @@ pub struct PrioritySearchIter<
     queue: PriorityQueue<T, TotalEstimate<N>>,
     costs: HashMap<T, N>,
     parents: HashMap<T, Option<T>>,
+    // Every predecessor on some cheapest path, when requested.
+    all_parents: Option<HashMap<T, Vec<T>>>,
     successor: S,
     heuristic: H,
 }
@@ -128,6 +583,9 @@ pub struct PrioritySearchIter<
 impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Option<N>>
     PrioritySearchIter<N, T, S, H>
 {
+    // Nodes are yielded in order of total estimate and never revisited, so
+    // costs are optimal only for a consistent heuristic. A heuristic of `None`
+    // prunes that node.
     pub fn a_star(start: T, successor: S, heuristic: H) -> Self {
         let mut queue = PriorityQueue::new();
         queue.push(start.clone(), TotalEstimate::default());
@@ -136,10 +594,72 @@ impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Opti
             costs: hash_map!(start.clone() => N::zero()),
             successor,
             parents: hash_map!(start.clone() => None),
+            all_parents: None,
             heuristic,
         }
     }
 
+    // Keeps every equal-cost predecessor rather than only the first one found.
+    // Call before iterating.
+    pub fn tracking_all_parents(mut self) -> Self {
+        self.all_parents = Some(HashMap::new());
+        self
+    }
+
+    // Predecessors of `node` on cheapest paths. Complete once `node` has been
+    // returned by the iterator. Empty unless `tracking_all_parents` was used.
+    pub fn parents_of(&self, node: &T) -> &[T] {
+        self.all_parents
+            .as_ref()
+            .and_then(|all| all.get(node))
+            .map_or(&[], |parents| parents.as_slice())
+    }
+
+    // Maps each node reached so far to its cheapest-path predecessors.
+    pub fn shortest_path_dag(&self) -> HashMap<T, Vec<T>> {
+        self.all_parents.clone().unwrap_or_default()
+    }
+
+    pub fn num_optimal_paths_to(&self, node: &T) -> usize {
+        let mut counts = HashMap::new();
+        self.count_paths(node, &mut counts)
+    }
+
+    fn count_paths(&self, node: &T, counts: &mut HashMap<T, usize>) -> usize {
+        if let Some(count) = counts.get(node) {
+            return *count;
+        }
+        let parents = self.parents_of(node);
+        let count = if parents.is_empty() {
+            1
+        } else {
+            parents.iter().map(|p| self.count_paths(p, counts)).sum()
+        };
+        counts.insert(node.clone(), count);
+        count
+    }
+
+    // Each path runs from `node` back to the start, like `path_back_from`.
+    pub fn all_optimal_paths_to(&self, node: &T) -> Vec<VecDeque<T>> {
+        let parents = self.parents_of(node);
+        if parents.is_empty() {
+            vec![VecDeque::from([node.clone()])]
+        } else {
+            parents
+                .iter()
+                .flat_map(|p| self.all_optimal_paths_to(p))
+                .map(|mut path| {
+                    path.push_front(node.clone());
+                    path
+                })
+                .collect()
+        }
+    }
+
+    pub fn nodes_on_optimal_paths_to<I: Iterator<Item = T>>(&self, targets: I) -> HashSet<T> {
+        BfsIter::multi_start(targets, |n| self.parents_of(n).to_vec()).collect()
+    }
+
     pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
         path_back_from(node, &self.parents)
     }
@@ -148,9 +668,47 @@ impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Opti
         self.costs.get(node).copied().unwrap()
     }
 
+    pub fn is_reachable(&self, node: &T) -> bool {
+        self.costs.contains_key(node)
+    }
+
     pub fn num_nodes_visited(&self) -> usize {
         self.costs.len()
     }
+
+    pub fn all_costs(&self) -> HashMap<T, N> {
+        self.costs.clone()
+    }
+
+    pub(crate) fn parents(&self) -> &HashMap<T, Option<T>> {
+        &self.parents
+    }
+}
+
+impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Option<N>>
+    PrioritySearchIter<N, T, S, H>
+{
+    fn record_parent(&mut self, parent: &T, child: &T, child_cost: N) {
+        if let Some(all_parents) = self.all_parents.as_mut() {
+            let best = self
+                .costs
+                .get(child)
+                .copied()
+                .or_else(|| self.queue.get_priority(child).map(|p| p.from_start));
+            match best {
+                Some(best) if child_cost > best => {}
+                Some(best) if child_cost == best => {
+                    let parents = all_parents.entry(child.clone()).or_default();
+                    if !parents.contains(parent) {
+                        parents.push(parent.clone());
+                    }
+                }
+                _ => {
+                    all_parents.insert(child.clone(), vec![parent.clone()]);
+                }
+            }
+        }
+    }
 }
 
 impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>>
@@ -172,10 +730,15 @@ impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Opti
             for (child, step_cost) in (self.successor)(&parent) {
                 if !self.costs.contains_key(&child) {
                     if let Some(estimate) = (self.heuristic)(&child) {
+                        // Parents are always finalized before their children, so
+                        // zero-cost edges cannot make the parent graph cyclic.
+                        self.record_parent(&parent, &child, cost.from_start + step_cost);
                         let new_priority = cost.next_cost(step_cost, estimate);
+                        // The heuristic depends only on `child`, so a queued entry
+                        // improves exactly when its cost from the start drops.
                         match self.queue.get_priority(&child) {
                             Some(priority) => {
-                                if new_priority > *priority {
+                                if new_priority.from_start < priority.from_start {
                                     self.parents.insert(child.clone(), Some(parent.clone()));
                                     self.queue.change_priority(&child, new_priority);
                                 }
@@ -193,29 +756,493 @@ impl<N: Estimator, T: SearchNode, S: FnMut(&T) -> Vec<(T, N)>, H: Fn(&T) -> Opti
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use enum_iterator::all;
+// Dijkstra for step costs of only 0 or 1, using a deque instead of a heap:
+// free steps go on the front and unit steps on the back. Panics if the
+// successor returns any other step cost.
+pub struct ZeroOneBfsIter<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> {
+    deque: VecDeque<(T, usize)>,
+    costs: HashMap<T, usize>,
+    parents: HashMap<T, Option<T>>,
+    num_visited: usize,
+    successor: S,
+}
 
-    use crate::{
-        grid::GridCharWorld,
-        multidim::{DirType, ManhattanDir, Position},
-        search_iter::{path_back_from, BfsIter, PrioritySearchIter},
-    };
+impl<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> ZeroOneBfsIter<T, S> {
+    pub fn new(start: T, successor: S) -> Self {
+        Self {
+            deque: VecDeque::from([(start.clone(), 0)]),
+            costs: hash_map!(start.clone() => 0),
+            parents: hash_map!(start => None),
+            num_visited: 0,
+            successor,
+        }
+    }
 
-    #[test]
-    fn test_bfs() {
-        println!("Test BFS");
-        let max_dist = 2;
-        let start = Position::default();
-        println!("Starting BFS");
-        let mut searcher = BfsIter::new(start, |n| {
-            all::<ManhattanDir>()
-                .map(move |d| d.neighbor(*n))
-                .filter(|p| start.manhattan_distance(p) <= max_dist)
-                .collect()
-        });
-        searcher.by_ref().last();
+    pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
+        path_back_from(node, &self.parents)
+    }
+
+    pub fn cost_for(&self, node: &T) -> usize {
+        self.costs.get(node).copied().unwrap()
+    }
+
+    pub fn is_reachable(&self, node: &T) -> bool {
+        self.costs.contains_key(node)
+    }
+
+    pub fn num_nodes_visited(&self) -> usize {
+        self.num_visited
+    }
+
+    pub fn all_costs(&self) -> HashMap<T, usize> {
+        self.costs.clone()
+    }
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> Iterator for ZeroOneBfsIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, cost)) = self.deque.pop_front() {
+            // A node is queued again whenever its cost improves, so only the
+            // entry matching its current cost is live.
+            if self.costs[&node] < cost {
+                continue;
+            }
+            self.num_visited += 1;
+            for (child, step_cost) in (self.successor)(&node) {
+                assert!(step_cost <= 1, "0-1 BFS step cost of {step_cost}");
+                let child_cost = cost + step_cost;
+                if self.costs.get(&child).is_none_or(|c| child_cost < *c) {
+                    self.costs.insert(child.clone(), child_cost);
+                    self.parents.insert(child.clone(), Some(node.clone()));
+                    if step_cost == 0 {
+                        self.deque.push_front((child, child_cost));
+                    } else {
+                        self.deque.push_back((child, child_cost));
+                    }
+                }
+            }
+            return Some(node);
+        }
+        None
+    }
+}
+
+// Dial's algorithm: Dijkstra over a circular array of buckets, one per cost,
+// for step costs no larger than `max_step`. Each pop is O(1) amortized.
+// Panics if the successor returns a step cost above `max_step`.
+pub struct DialIter<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> {
+    buckets: Vec<Vec<T>>,
+    current: usize,
+    num_queued: usize,
+    costs: HashMap<T, usize>,
+    parents: HashMap<T, Option<T>>,
+    successor: S,
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> DialIter<T, S> {
+    pub fn new(start: T, max_step: usize, successor: S) -> Self {
+        let mut buckets = vec![vec![]; max_step + 1];
+        buckets[0].push(start.clone());
+        Self {
+            buckets,
+            current: 0,
+            num_queued: 1,
+            costs: hash_map!(start.clone() => 0),
+            parents: hash_map!(start => None),
+            successor,
+        }
+    }
+
+    pub fn path_back_from(&self, node: &T) -> VecDeque<T> {
+        path_back_from(node, &self.parents)
+    }
+
+    pub fn cost_for(&self, node: &T) -> usize {
+        self.costs.get(node).copied().unwrap()
+    }
+
+    pub fn is_reachable(&self, node: &T) -> bool {
+        self.costs.contains_key(node)
+    }
+
+    pub fn all_costs(&self) -> HashMap<T, usize> {
+        self.costs.clone()
+    }
+}
+
+impl<T: SearchNode, S: FnMut(&T) -> Vec<(T, usize)>> Iterator for DialIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.num_queued > 0 {
+            let num_buckets = self.buckets.len();
+            match self.buckets[self.current % num_buckets].pop() {
+                None => self.current += 1,
+                Some(node) => {
+                    self.num_queued -= 1;
+                    // Entries left behind when a node's cost improved are skipped.
+                    if self.costs[&node] != self.current {
+                        continue;
+                    }
+                    for (child, step_cost) in (self.successor)(&node) {
+                        assert!(
+                            step_cost < num_buckets,
+                            "step cost {step_cost} exceeds bucket range"
+                        );
+                        let child_cost = self.current + step_cost;
+                        if self.costs.get(&child).is_none_or(|c| child_cost < *c) {
+                            self.costs.insert(child.clone(), child_cost);
+                            self.parents.insert(child.clone(), Some(node.clone()));
+                            self.buckets[child_cost % num_buckets].push(child);
+                            self.num_queued += 1;
+                        }
+                    }
+                    return Some(node);
+                }
+            }
+        }
+        None
+    }
+}
+
+// IDA*: iterative deepening on the total estimate. The heuristic must never
+// overestimate. Returns a cheapest path from start to a goal with its cost.
+pub fn ida_star<
+    N: Estimator,
+    T: SearchNode,
+    S: FnMut(&T) -> Vec<(T, N)>,
+    H: Fn(&T) -> N,
+    G: Fn(&T) -> bool,
+>(
+    start: T,
+    mut successor: S,
+    heuristic: H,
+    is_goal: G,
+) -> Option<(Vec<T>, N)> {
+    let mut bound = heuristic(&start);
+    let mut path = vec![start];
+    loop {
+        match bounded_search(
+            &mut path,
+            N::zero(),
+            bound,
+            &mut successor,
+            &heuristic,
+            &is_goal,
+        ) {
+            Bounded::Found(cost) => return Some((path, cost)),
+            Bounded::Exceeded(next_bound) => bound = next_bound,
+            Bounded::Exhausted => return None,
+        }
+    }
+}
+
+enum Bounded<N> {
+    Found(N),
+    // The smallest total estimate that went past the bound.
+    Exceeded(N),
+    Exhausted,
+}
+
+fn bounded_search<
+    N: Estimator,
+    T: SearchNode,
+    S: FnMut(&T) -> Vec<(T, N)>,
+    H: Fn(&T) -> N,
+    G: Fn(&T) -> bool,
+>(
+    path: &mut Vec<T>,
+    cost: N,
+    bound: N,
+    successor: &mut S,
+    heuristic: &H,
+    is_goal: &G,
+) -> Bounded<N> {
+    let node = path.last().unwrap().clone();
+    let total = cost + heuristic(&node);
+    if total > bound {
+        return Bounded::Exceeded(total);
+    }
+    if is_goal(&node) {
+        return Bounded::Found(cost);
+    }
+    let mut result = Bounded::Exhausted;
+    for (child, step_cost) in successor(&node) {
+        if !path.contains(&child) {
+            path.push(child);
+            match bounded_search(path, cost + step_cost, bound, successor, heuristic, is_goal) {
+                Bounded::Found(found) => return Bounded::Found(found),
+                Bounded::Exceeded(exceeded) => {
+                    result = match result {
+                        Bounded::Exceeded(current) if current <= exceeded => result,
+                        _ => Bounded::Exceeded(exceeded),
+                    }
+                }
+                Bounded::Exhausted => {}
+            }
+            path.pop();
+        }
+    }
+    result
+}
+
+// One side of a bidirectional search: Dijkstra from a set of sources.
+struct Frontier<N: Estimator, T: SearchNode> {
+    queue: PriorityQueue<T, Reverse<N>>,
+    costs: HashMap<T, N>,
+    parents: HashMap<T, Option<T>>,
+}
+
+impl<N: Estimator, T: SearchNode> Frontier<N, T> {
+    fn new<I: Iterator<Item = T>>(sources: I) -> Self {
+        let mut result = Self {
+            queue: PriorityQueue::new(),
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+        };
+        for source in sources {
+            result.queue.push(source.clone(), Reverse(N::zero()));
+            result.costs.insert(source.clone(), N::zero());
+            result.parents.insert(source, None);
+        }
+        result
+    }
+
+    fn next_cost(&self) -> Option<N> {
+        self.queue.peek().map(|(_, Reverse(cost))| *cost)
+    }
+
+    // Settles the cheapest queued node, returning every node whose cost improved.
+    fn expand<S: FnMut(&T) -> Vec<(T, N)>>(&mut self, successor: &mut S) -> Vec<T> {
+        let mut improved = vec![];
+        if let Some((node, Reverse(cost))) = self.queue.pop() {
+            for (child, step_cost) in successor(&node) {
+                let child_cost = cost + step_cost;
+                if self.costs.get(&child).is_none_or(|c| child_cost < *c) {
+                    self.costs.insert(child.clone(), child_cost);
+                    self.parents.insert(child.clone(), Some(node.clone()));
+                    self.queue.push(child.clone(), Reverse(child_cost));
+                    improved.push(child);
+                }
+            }
+        }
+        improved
+    }
+
+    fn exhaust<S: FnMut(&T) -> Vec<(T, N)>>(mut self, successor: &mut S) -> HashMap<T, N> {
+        while !self.queue.is_empty() {
+            self.expand(successor);
+        }
+        self.costs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BidirectionalPath<T, N> {
+    meeting: T,
+    cost: N,
+    path: Vec<T>,
+}
+
+impl<T, N: Copy> BidirectionalPath<T, N> {
+    // Where the forward and backward searches joined up.
+    pub fn meeting(&self) -> &T {
+        &self.meeting
+    }
+
+    pub fn cost(&self) -> N {
+        self.cost
+    }
+
+    // From a start to a goal, passing through the meeting node.
+    pub fn path(&self) -> &[T] {
+        &self.path
+    }
+}
+
+// Searches forward from `starts` and backward from `goals` at the same time.
+// `reverse` must return the predecessors of a node, with the same step costs
+// that `forward` gives for the matching edges.
+pub fn bidirectional_dijkstra<
+    N: Estimator,
+    T: SearchNode,
+    I: Iterator<Item = T>,
+    J: Iterator<Item = T>,
+    F: FnMut(&T) -> Vec<(T, N)>,
+    R: FnMut(&T) -> Vec<(T, N)>,
+>(
+    starts: I,
+    goals: J,
+    mut forward: F,
+    mut reverse: R,
+) -> Option<BidirectionalPath<T, N>> {
+    let mut ahead = Frontier::new(starts);
+    let mut behind = Frontier::new(goals);
+    let mut best: Option<(N, T)> = None;
+    let meet =
+        |node: T, ahead: &Frontier<N, T>, behind: &Frontier<N, T>, best: &mut Option<(N, T)>| {
+            if let (Some(a), Some(b)) = (ahead.costs.get(&node), behind.costs.get(&node)) {
+                if best.as_ref().is_none_or(|(cost, _)| *a + *b < *cost) {
+                    *best = Some((*a + *b, node));
+                }
+            }
+        };
+    for node in ahead.costs.keys() {
+        meet(node.clone(), &ahead, &behind, &mut best);
+    }
+    loop {
+        let (next_ahead, next_behind) = (ahead.next_cost(), behind.next_cost());
+        // Once the two frontiers together cost at least as much as the best
+        // meeting so far, no undiscovered path can beat it.
+        let finished = match (next_ahead, next_behind, &best) {
+            (None, None, _) => true,
+            (Some(a), Some(b), Some((cost, _))) => a + b >= *cost,
+            (_, _, Some(_)) => true,
+            _ => false,
+        };
+        if finished {
+            break;
+        }
+        let forward_turn = match (next_ahead, next_behind) {
+            (Some(a), Some(b)) => a <= b,
+            (a, _) => a.is_some(),
+        };
+        if forward_turn {
+            for node in ahead.expand(&mut forward) {
+                meet(node, &ahead, &behind, &mut best);
+            }
+        } else {
+            for node in behind.expand(&mut reverse) {
+                meet(node, &ahead, &behind, &mut best);
+            }
+        }
+    }
+    best.map(|(cost, meeting)| {
+        let mut path = path_back_from(&meeting, &ahead.parents)
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>();
+        path.extend(
+            path_back_from(&meeting, &behind.parents)
+                .into_iter()
+                .skip(1),
+        );
+        BidirectionalPath {
+            meeting,
+            cost,
+            path,
+        }
+    })
+}
+
+// Bidirectional search where every step costs 1.
+pub fn bidirectional_bfs<
+    T: SearchNode,
+    I: Iterator<Item = T>,
+    J: Iterator<Item = T>,
+    F: FnMut(&T) -> Vec<T>,
+    R: FnMut(&T) -> Vec<T>,
+>(
+    starts: I,
+    goals: J,
+    mut forward: F,
+    mut reverse: R,
+) -> Option<BidirectionalPath<T, usize>> {
+    bidirectional_dijkstra(
+        starts,
+        goals,
+        |n| forward(n).into_iter().map(|m| (m, 1)).collect(),
+        |n| reverse(n).into_iter().map(|m| (m, 1)).collect(),
+    )
+}
+
+// Complete cost maps from the starts and to the goals, for asking how much a
+// route through a particular node costs.
+#[derive(Clone, Debug)]
+pub struct BidirectionalDistances<T: SearchNode, N: Estimator> {
+    from_start: HashMap<T, N>,
+    to_goal: HashMap<T, N>,
+}
+
+impl<T: SearchNode, N: Estimator> BidirectionalDistances<T, N> {
+    pub fn new<
+        I: Iterator<Item = T>,
+        J: Iterator<Item = T>,
+        F: FnMut(&T) -> Vec<(T, N)>,
+        R: FnMut(&T) -> Vec<(T, N)>,
+    >(
+        starts: I,
+        goals: J,
+        mut forward: F,
+        mut reverse: R,
+    ) -> Self {
+        Self {
+            from_start: Frontier::new(starts).exhaust(&mut forward),
+            to_goal: Frontier::new(goals).exhaust(&mut reverse),
+        }
+    }
+
+    pub fn from_start(&self, node: &T) -> Option<N> {
+        self.from_start.get(node).copied()
+    }
+
+    pub fn to_goal(&self, node: &T) -> Option<N> {
+        self.to_goal.get(node).copied()
+    }
+
+    // The cheapest cost of a start-to-goal route through `node`.
+    pub fn via(&self, node: &T) -> Option<N> {
+        Some(self.from_start(node)? + self.to_goal(node)?)
+    }
+
+    // The cheapest start-to-goal cost overall.
+    pub fn shortest(&self) -> Option<N> {
+        self.from_start.keys().filter_map(|n| self.via(n)).min()
+    }
+
+    pub fn all_from_start(&self) -> &HashMap<T, N> {
+        &self.from_start
+    }
+
+    pub fn all_to_goal(&self) -> &HashMap<T, N> {
+        &self.to_goal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cmp::Ordering,
+        collections::{HashMap, HashSet, VecDeque},
+    };
+
+    use enum_iterator::all;
+
+    use crate::{
+        grid::GridCharWorld,
+        multidim::{DirType, ManhattanDir, Position},
+        search_iter::{
+            bidirectional_bfs, bidirectional_dijkstra, ida_star, iddfs, path_back_from, BfsIter,
+            BidirectionalDistances, DfsEvent, DfsIter, DialIter, Lexicographic, ParallelBfsIter,
+            PrioritySearchIter, SimplePaths, TotalEstimate, TotalF64, ZeroOneBfsIter,
+        },
+    };
+
+    #[test]
+    fn test_bfs() {
+        println!("Test BFS");
+        let max_dist = 2;
+        let start = Position::default();
+        println!("Starting BFS");
+        let mut searcher = BfsIter::new(start, |n| {
+            all::<ManhattanDir>()
+                .map(move |d| d.neighbor(*n))
+                .filter(|p| start.manhattan_distance(p) <= max_dist)
+                .collect()
+        });
+        searcher.by_ref().last();
         println!("Search complete.");
         assert_eq!(searcher.parents.len(), 13);
 
@@ -281,4 +1308,531 @@ mod tests {
         let path = searcher.path_back_from(&result);
         println!("{path:?}");
     }
+
+    #[test]
+    fn test_all_optimal_paths() {
+        // Every monotone path through a 3x3 grid has the same length.
+        let start = Position::default();
+        let corner = Position::from((2, 2));
+        let mut searcher = PrioritySearchIter::dijkstra(start, |p| {
+            [
+                Position::from((p[0] + 1, p[1])),
+                Position::from((p[0], p[1] + 1)),
+            ]
+            .into_iter()
+            .filter(|c| c[0] <= 2 && c[1] <= 2)
+            .map(|c| (c, 1))
+            .collect()
+        })
+        .tracking_all_parents();
+        searcher.by_ref().last();
+        assert_eq!(searcher.num_optimal_paths_to(&corner), 6);
+        let paths = searcher.all_optimal_paths_to(&corner);
+        assert_eq!(paths.len(), 6);
+        assert!(paths.iter().all(|path| path.len() == 5
+            && path.front() == Some(&corner)
+            && path.back() == Some(&start)));
+        assert_eq!(searcher.parents_of(&corner).len(), 2);
+        assert_eq!(searcher.parents_of(&start).len(), 0);
+        let on_paths = searcher.nodes_on_optimal_paths_to([Position::from((1, 1))].into_iter());
+        assert_eq!(on_paths.len(), 4);
+        assert_eq!(searcher.shortest_path_dag().len(), 8);
+    }
+
+    #[test]
+    fn test_optimal_parents_discard_worse() {
+        // 0 -> 1 -> 3 and 0 -> 2 -> 3 tie at 4; 0 -> 3 directly costs 5.
+        let edges = [
+            vec![(1, 2), (2, 1), (3, 5)],
+            vec![(3, 2)],
+            vec![(3, 3)],
+            vec![],
+        ];
+        let mut searcher =
+            PrioritySearchIter::dijkstra(0, |n: &usize| edges[*n].clone()).tracking_all_parents();
+        searcher.by_ref().last();
+        assert_eq!(searcher.cost_for(&3), 4);
+        let mut parents = searcher.parents_of(&3).to_vec();
+        parents.sort();
+        assert_eq!(parents, vec![1, 2]);
+        assert_eq!(searcher.num_optimal_paths_to(&3), 2);
+    }
+
+    #[test]
+    fn test_optimal_parents_zero_cost_and_repeats() {
+        // 1 and 2 are joined by free edges both ways, 0 lists 1 twice, and 3
+        // is pruned by the heuristic.
+        let edges = [
+            vec![(1, 1), (1, 1), (2, 1), (3, 1)],
+            vec![(2, 0), (4, 1)],
+            vec![(1, 0), (4, 1)],
+            vec![(4, 0)],
+            vec![],
+        ];
+        let mut searcher = PrioritySearchIter::a_star(
+            0,
+            |n: &usize| edges[*n].clone(),
+            |n| if *n == 3 { None } else { Some(0) },
+        )
+        .tracking_all_parents();
+        searcher.by_ref().last();
+        // Whichever of 1 and 2 is finalized first becomes a parent of the other.
+        assert_eq!(
+            searcher.parents_of(&1).len() + searcher.parents_of(&2).len(),
+            3
+        );
+        assert_eq!(searcher.cost_for(&4), 2);
+        assert_eq!(searcher.num_optimal_paths_to(&4), 3);
+        assert_eq!(searcher.all_optimal_paths_to(&4).len(), 3);
+        assert!(!searcher.shortest_path_dag().contains_key(&3));
+    }
+
+    // Small xorshift generator so the property tests are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+    }
+
+    // Cell weights are the cost of entering a cell; 0 marks a wall.
+    fn random_grid(rng: &mut Rng, width: usize, height: usize) -> Vec<Vec<usize>> {
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        if rng.below(5) == 0 {
+                            0
+                        } else {
+                            1 + rng.below(9) as usize
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn grid_successors(grid: &[Vec<usize>], p: Position) -> Vec<(Position, usize)> {
+        all::<ManhattanDir>()
+            .map(|d| d.neighbor(p))
+            .filter_map(|n| {
+                let row = grid.get(usize::try_from(n[1]).ok()?)?;
+                let weight = *row.get(usize::try_from(n[0]).ok()?)?;
+                (weight > 0).then_some((n, weight))
+            })
+            .collect()
+    }
+
+    // Relaxes every edge until nothing changes.
+    fn brute_force_costs(grid: &[Vec<usize>], start: Position) -> HashMap<Position, usize> {
+        let mut costs = HashMap::from([(start, 0)]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (p, cost) in costs.clone() {
+                for (n, weight) in grid_successors(grid, p) {
+                    if costs.get(&n).is_none_or(|c| cost + weight < *c) {
+                        costs.insert(n, cost + weight);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        costs
+    }
+
+    // Runs `check` on random grids with the start (0, 0) and the goal in the
+    // opposite corner both open, along with the brute-force costs from the start.
+    fn on_random_grids<F>(seed: u64, trials: usize, max_side: u64, mut check: F)
+    where
+        F: FnMut(&[Vec<usize>], Position, Position, &HashMap<Position, usize>),
+    {
+        let mut rng = Rng(seed);
+        for _ in 0..trials {
+            let width = 2 + rng.below(max_side) as usize;
+            let height = 2 + rng.below(max_side) as usize;
+            let mut grid = random_grid(&mut rng, width, height);
+            grid[0][0] = 1;
+            grid[height - 1][width - 1] = 1;
+            let start = Position::default();
+            let goal = Position::from((width as isize - 1, height as isize - 1));
+            let expected = brute_force_costs(&grid, start);
+            check(&grid, start, goal, &expected);
+        }
+    }
+
+    fn assert_nondecreasing<F: Fn(&Position) -> usize>(order: &[Position], cost_for: F) {
+        assert!(order.windows(2).all(|w| cost_for(&w[0]) <= cost_for(&w[1])));
+    }
+
+    fn path_cost(grid: &[Vec<usize>], path: &VecDeque<Position>) -> usize {
+        path.iter()
+            .take(path.len() - 1)
+            .map(|p| grid[p[1] as usize][p[0] as usize])
+            .sum()
+    }
+
+    #[test]
+    fn test_total_estimate_ordering() {
+        let a = TotalEstimate {
+            from_start: 3,
+            estimate_to_goal: 4,
+        };
+        let b = TotalEstimate {
+            from_start: 5,
+            estimate_to_goal: 2,
+        };
+        let c = TotalEstimate {
+            from_start: 1,
+            estimate_to_goal: 9,
+        };
+        assert!(a > c);
+        assert!(b > a);
+        assert_eq!(a.cmp(&b), a.partial_cmp(&b).unwrap());
+        assert_eq!(a.cmp(&a), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_dijkstra_matches_brute_force() {
+        on_random_grids(0x2024, 50, 9, |grid, start, _, expected| {
+            let mut searcher = PrioritySearchIter::dijkstra(start, |p| grid_successors(grid, *p));
+            let order = searcher.by_ref().collect::<Vec<_>>();
+            assert_eq!(searcher.all_costs(), *expected);
+            assert_nondecreasing(&order, |p| searcher.cost_for(p));
+            for (p, cost) in expected.iter() {
+                assert_eq!(path_cost(grid, &searcher.path_back_from(p)), *cost);
+            }
+        });
+    }
+
+    #[test]
+    fn test_a_star_matches_brute_force() {
+        on_random_grids(0xa57a, 50, 9, |grid, start, goal, expected| {
+            // Every step costs at least 1, so Manhattan distance is consistent.
+            let mut searcher = PrioritySearchIter::a_star(
+                start,
+                |p| grid_successors(grid, *p),
+                |p| Some(goal.manhattan_distance(p) as usize),
+            );
+            match searcher.by_ref().find(|p| *p == goal) {
+                Some(found) => {
+                    assert_eq!(searcher.cost_for(&found), expected[&goal]);
+                    let path = searcher.path_back_from(&found);
+                    assert_eq!(path_cost(grid, &path), expected[&goal]);
+                }
+                None => assert!(!expected.contains_key(&goal)),
+            }
+        });
+    }
+
+    #[test]
+    fn test_float_costs() {
+        // A diagonal step costs sqrt(2), so going around the corner is longer.
+        let diagonal = TotalF64(2.0_f64.sqrt());
+        let mut searcher = PrioritySearchIter::dijkstra(Position::default(), |p| {
+            let mut result = vec![];
+            if p[0] < 3 {
+                result.push((Position::from((p[0] + 1, p[1])), TotalF64(1.0)));
+            }
+            if p[1] < 3 {
+                result.push((Position::from((p[0], p[1] + 1)), TotalF64(1.0)));
+            }
+            if p[0] < 3 && p[1] < 3 {
+                result.push((Position::from((p[0] + 1, p[1] + 1)), diagonal));
+            }
+            result
+        });
+        let goal = Position::from((3, 3));
+        searcher.by_ref().find(|p| *p == goal).unwrap();
+        let cost = searcher.cost_for(&goal).0;
+        assert!((cost - 3.0 * 2.0_f64.sqrt()).abs() < 1e-9);
+        assert_eq!(searcher.path_back_from(&goal).len(), 4);
+        assert!(TotalF64(-0.0) < TotalF64(0.0));
+        assert!(TotalF64(f64::NAN) > TotalF64(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_lexicographic_costs() {
+        // 0 -> 1 -> 3 has fewer turns but more steps than 0 -> 2 -> 3.
+        let edges = [
+            vec![(1, Lexicographic(0, 10)), (2, Lexicographic(1, 1))],
+            vec![(3, Lexicographic(0, 10))],
+            vec![(3, Lexicographic(0, 1))],
+            vec![],
+        ];
+        let mut searcher = PrioritySearchIter::dijkstra(0, |n: &usize| edges[*n].clone());
+        searcher.by_ref().last();
+        assert_eq!(searcher.cost_for(&3), Lexicographic(0, 20));
+        assert_eq!(searcher.path_back_from(&3), VecDeque::from([3, 1, 0]));
+        assert_eq!(
+            Lexicographic::from((1, 2)) + Lexicographic(3, 4),
+            Lexicographic(4, 6)
+        );
+    }
+
+    #[test]
+    fn test_dfs() {
+        // 0 -> 1 -> 3, 0 -> 2 -> 3, 3 -> 0
+        let edges = [vec![1, 2], vec![3], vec![3], vec![0]];
+        let mut searcher = DfsIter::new(0, |n: &usize| edges[*n].clone());
+        assert_eq!(searcher.by_ref().collect::<Vec<_>>(), vec![0, 1, 3, 2]);
+        assert_eq!(searcher.depth_for(&3), 2);
+        assert_eq!(searcher.path_back_from(&3), VecDeque::from([3, 1, 0]));
+        assert_eq!(searcher.path_back_from(&2), VecDeque::from([2, 0]));
+
+        let events = DfsIter::new(0, |n: &usize| edges[*n].clone())
+            .events()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                DfsEvent::Enter(0),
+                DfsEvent::Enter(1),
+                DfsEvent::Enter(3),
+                DfsEvent::Exit(3),
+                DfsEvent::Exit(1),
+                DfsEvent::Enter(2),
+                DfsEvent::Exit(2),
+                DfsEvent::Exit(0),
+            ]
+        );
+
+        let roots = DfsIter::multi_start([2, 4, 0].into_iter(), |n: &usize| {
+            edges.get(*n).cloned().unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(roots, vec![2, 3, 0, 1, 4]);
+    }
+
+    #[test]
+    fn test_simple_paths() {
+        // From one corner of a complete graph on 4 nodes: 1 + 3 + 6 + 6 paths.
+        let paths = SimplePaths::new(0, |n: &usize| (0..4).filter(|m| m != n).collect())
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), 16);
+        assert_eq!(paths[0], vec![0]);
+        assert_eq!(paths.iter().filter(|p| p.len() == 4).count(), 6);
+        assert!(paths
+            .iter()
+            .all(|p| p.iter().collect::<HashSet<_>>().len() == p.len()));
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        let max_dist = 3;
+        let start = Position::default();
+        let goal = Position::from((2, -1));
+        let successor = |p: &Position| {
+            all::<ManhattanDir>()
+                .map(|d| d.neighbor(*p))
+                .filter(|n| n.manhattan_distance(&start) <= max_dist)
+                .collect::<Vec<_>>()
+        };
+        let path = iddfs(start, 10, successor, |p| *p == goal).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        assert!(iddfs(start, 2, successor, |p| *p == goal).is_none());
+        assert!(iddfs(start, 100, successor, |p| p[0] > 5).is_none());
+    }
+
+    #[test]
+    fn test_ida_star_matches_dijkstra() {
+        on_random_grids(0x1da, 20, 5, |grid, start, goal, expected| {
+            let found = ida_star(
+                start,
+                |p| grid_successors(grid, *p),
+                |p| goal.manhattan_distance(p) as usize,
+                |p| *p == goal,
+            );
+            assert_eq!(
+                found.as_ref().map(|(_, cost)| *cost),
+                expected.get(&goal).copied()
+            );
+            if let Some((path, cost)) = found {
+                let back = path.iter().rev().copied().collect::<VecDeque<_>>();
+                assert_eq!(path_cost(grid, &back), cost);
+            }
+        });
+    }
+
+    // Predecessors of `p`, each charged the cost of entering `p`.
+    fn grid_predecessors(grid: &[Vec<usize>], p: Position) -> Vec<(Position, usize)> {
+        let weight = grid[p[1] as usize][p[0] as usize];
+        grid_successors(grid, p)
+            .into_iter()
+            .map(|(n, _)| (n, weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra_matches_brute_force() {
+        on_random_grids(0xb1d1, 50, 9, |grid, start, goal, expected| {
+            let expected = expected.get(&goal).copied();
+            let found = bidirectional_dijkstra(
+                [start].into_iter(),
+                [goal].into_iter(),
+                |p| grid_successors(grid, *p),
+                |p| grid_predecessors(grid, *p),
+            );
+            assert_eq!(found.as_ref().map(|f| f.cost()), expected);
+            let distances = BidirectionalDistances::new(
+                [start].into_iter(),
+                [goal].into_iter(),
+                |p| grid_successors(grid, *p),
+                |p| grid_predecessors(grid, *p),
+            );
+            assert_eq!(distances.shortest(), expected);
+            if let Some(found) = found {
+                let path = found.path();
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&goal));
+                assert!(path.contains(found.meeting()));
+                assert!(path.windows(2).all(|w| w[0].manhattan_distance(&w[1]) == 1));
+                let back = path.iter().rev().copied().collect::<VecDeque<_>>();
+                assert_eq!(path_cost(grid, &back), found.cost());
+                assert_eq!(distances.via(found.meeting()), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_bidirectional_bfs() {
+        let max_dist = 4;
+        let start = Position::default();
+        let neighbors = |p: &Position| {
+            all::<ManhattanDir>()
+                .map(|d| d.neighbor(*p))
+                .filter(|n| n.manhattan_distance(&start) <= max_dist)
+                .collect::<Vec<_>>()
+        };
+        let goals = [Position::from((3, 1)), Position::from((-2, 0))];
+        let found = bidirectional_bfs([start].into_iter(), goals.into_iter(), neighbors, neighbors)
+            .unwrap();
+        assert_eq!(found.cost(), 2);
+        assert_eq!(found.path().len(), 3);
+        assert_eq!(found.path().last(), Some(&goals[1]));
+
+        let same = bidirectional_bfs(
+            [start].into_iter(),
+            [start].into_iter(),
+            neighbors,
+            neighbors,
+        )
+        .unwrap();
+        assert_eq!(same.cost(), 0);
+        assert_eq!(same.path(), &[start]);
+
+        let outside = Position::from((10, 10));
+        assert!(bidirectional_bfs(
+            [start].into_iter(),
+            [outside].into_iter(),
+            neighbors,
+            neighbors
+        )
+        .is_none());
+    }
+
+    fn open_plane(p: &Position) -> Vec<Position> {
+        all::<ManhattanDir>().map(|d| d.neighbor(*p)).collect()
+    }
+
+    #[test]
+    fn test_bfs_limits() {
+        let start = Position::default();
+        let mut searcher = BfsIter::new(start, open_plane).with_max_depth(2);
+        assert_eq!(searcher.by_ref().count(), 13);
+        assert_eq!(searcher.depth_of(&Position::from((1, 1))), Some(2));
+        assert_eq!(searcher.depth_of(&Position::from((3, 0))), None);
+        assert_eq!(searcher.path_to(&Position::from((3, 0))), None);
+        assert!(!searcher.budget_exhausted());
+
+        let mut searcher = BfsIter::new(start, open_plane).with_node_budget(5);
+        assert_eq!(searcher.by_ref().count(), 5);
+        assert!(searcher.budget_exhausted());
+        assert_eq!(searcher.next(), None);
+    }
+
+    #[test]
+    fn test_bfs_find_goal() {
+        let start = Position::default();
+        let goal = Position::from((2, -3));
+        let (path, depth) = BfsIter::new(start, open_plane)
+            .find_goal(|p| *p == goal)
+            .unwrap();
+        assert_eq!(depth, 5);
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.front(), Some(&start));
+        assert_eq!(path.back(), Some(&goal));
+
+        let mut bounded = BfsIter::new(start, open_plane).with_max_depth(4);
+        assert!(bounded.find_goal(|p| *p == goal).is_none());
+        let mut budgeted = BfsIter::new(start, open_plane).with_node_budget(20);
+        assert!(budgeted.find_goal(|p| *p == goal).is_none());
+        assert!(budgeted.budget_exhausted());
+    }
+
+    #[test]
+    fn test_zero_one_bfs_matches_dijkstra() {
+        on_random_grids(0x01bf, 50, 9, |grid, start, _, _| {
+            // Odd weights become free steps and even ones unit steps.
+            let free_or_unit = |p: &Position| {
+                grid_successors(grid, *p)
+                    .into_iter()
+                    .map(|(n, w)| (n, 1 - w % 2))
+                    .collect()
+            };
+            let mut dijkstra = PrioritySearchIter::dijkstra(start, free_or_unit);
+            dijkstra.by_ref().last();
+            let expected = dijkstra.all_costs();
+            let mut searcher = ZeroOneBfsIter::new(start, free_or_unit);
+            let order = searcher.by_ref().collect::<Vec<_>>();
+            assert_eq!(searcher.all_costs(), expected);
+            assert_eq!(order.len(), expected.len());
+            assert_nondecreasing(&order, |p| searcher.cost_for(p));
+        });
+    }
+
+    #[test]
+    fn test_dial_matches_brute_force() {
+        on_random_grids(0xd1a1, 50, 9, |grid, start, _, expected| {
+            let mut searcher = DialIter::new(start, 9, |p| grid_successors(grid, *p));
+            let order = searcher.by_ref().collect::<Vec<_>>();
+            assert_eq!(searcher.all_costs(), *expected);
+            assert_eq!(order.len(), expected.len());
+            assert_nondecreasing(&order, |p| searcher.cost_for(p));
+            for (p, cost) in expected.iter() {
+                assert_eq!(path_cost(grid, &searcher.path_back_from(p)), *cost);
+            }
+        });
+    }
+
+    #[test]
+    fn test_parallel_bfs_matches_bfs() {
+        let max_dist = 30;
+        let start = Position::default();
+        let successor = |p: &Position| {
+            all::<ManhattanDir>()
+                .map(|d| d.neighbor(*p))
+                .filter(|n| n.manhattan_distance(&start) <= max_dist && n[0] % 7 != 3)
+                .collect::<Vec<_>>()
+        };
+        let mut sequential = BfsIter::new(start, successor);
+        let order = sequential.by_ref().collect::<Vec<_>>();
+        let mut parallel = ParallelBfsIter::new(start, successor);
+        let levels = parallel.by_ref().collect::<Vec<_>>();
+        assert_eq!(levels.concat(), order);
+        assert_eq!(parallel.all_depths(), sequential.all_depths());
+        for (depth, level) in levels.iter().enumerate() {
+            for p in level {
+                assert_eq!(parallel.depth_of(p), Some(depth));
+                assert_eq!(parallel.path_to(p).unwrap().len(), depth + 1);
+            }
+        }
+        assert_eq!(parallel.depth_of(&Position::from((3, 0))), None);
+    }
 }